@@ -0,0 +1,43 @@
+#!/usr/bin/env node
+
+/**
+ * Build script for PASTE_THIS_INTO_PLAYGROUND.rs
+ * Solana Playground cannot build path dependencies, so the program's lib.rs is
+ * written out with crates/wordle-rules inlined as a `wordle_rules` module (tests dropped)
+ */
+
+const fs = require('fs');
+const path = require('path');
+
+const rulesDir = path.join(__dirname, 'crates', 'wordle-rules', 'src');
+const programPath = path.join(__dirname, 'programs', 'wordle-escrow', 'src', 'lib.rs');
+const outputPath = path.join(__dirname, 'PASTE_THIS_INTO_PLAYGROUND.rs');
+
+function withoutTests(source) {
+  return source.split('\n#[cfg(test)]')[0];
+}
+
+function indent(source) {
+  return source
+    .replace(/\n+$/, '')
+    .split('\n')
+    .map(line => (line ? '    ' + line : ''))
+    .join('\n');
+}
+
+let rules = withoutTests(fs.readFileSync(path.join(rulesDir, 'lib.rs'), 'utf8')).replace('#![no_std]\n', '');
+for (const [declaration, name] of rules.matchAll(/^pub mod (\w+);$/gm)) {
+  const module = withoutTests(fs.readFileSync(path.join(rulesDir, `${name}.rs`), 'utf8'));
+  rules = rules.replace(`${declaration}\n`, `pub mod ${name} {\n${indent(module)}\n}\n`);
+}
+
+const program = withoutTests(fs.readFileSync(programPath, 'utf8'));
+
+fs.writeFileSync(
+  outputPath,
+  '// Single-file copy for Solana Playground, which cannot build path dependencies\n' +
+    '// crates/wordle-rules is inlined below; regenerate with `npm run build:playground` rather than editing it\n' +
+    `mod wordle_rules {\n${indent(rules)}\n}\n\n${program.replace(/\n*$/, '\n')}`
+);
+
+console.log(`✅ Wrote ${path.relative(__dirname, outputPath)}`);
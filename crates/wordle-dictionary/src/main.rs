@@ -0,0 +1,200 @@
+// Builds the Merkle tree for a word list (see wordle_rules::merkle)
+// Usage: wordle-dictionary [--indexed] <words.txt> <out.json>
+// Prints the root to pass to initialize_config / update_config and writes the root, word count and every
+// word's proof as JSON: {"root": "<hex>", "count": N, "proofs": {"WORD": ["<hex>", ...]}}
+// Answer lists use --indexed so each leaf also commits to the word's position in the sorted list
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::{env, fs, process};
+use wordle_rules::merkle::{self, Hash};
+use wordle_rules::WORD_LENGTH;
+
+fn sha256(parts: &[&[u8]]) -> Hash {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+// One word per line; blank lines and # comments are skipped, words are uppercased, deduplicated and sorted
+fn parse_words(text: &str) -> Result<Vec<String>, String> {
+    let mut words = BTreeSet::new();
+    for (number, line) in text.lines().enumerate() {
+        let word = line.trim().to_ascii_uppercase();
+        if word.is_empty() || word.starts_with('#') {
+            continue;
+        }
+        if word.len() != WORD_LENGTH || !wordle_rules::is_valid_word(word.as_bytes()) {
+            return Err(format!("line {}: \"{}\" is not a {}-letter word", number + 1, line.trim(), WORD_LENGTH));
+        }
+        words.insert(word);
+    }
+    if words.is_empty() {
+        return Err("word list is empty".into());
+    }
+    Ok(words.into_iter().collect())
+}
+
+fn leaf(index: usize, word: &str, indexed: bool) -> Hash {
+    if indexed {
+        let word: [u8; WORD_LENGTH] = word.as_bytes().try_into().unwrap();
+        merkle::leaf_hash(&sha256, &merkle::answer_leaf_data(index as u32, &word))
+    } else {
+        merkle::leaf_hash(&sha256, word.as_bytes())
+    }
+}
+
+// Every level of the tree, leaves first and the root last
+fn build_levels(words: &[String], indexed: bool) -> Vec<Vec<Hash>> {
+    let leaves = words.iter().enumerate().map(|(i, w)| leaf(i, w, indexed)).collect::<Vec<_>>();
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => merkle::node_hash(&sha256, a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+// Sibling hashes from the leaf at `index` up to the root
+fn proof(levels: &[Vec<Hash>], mut index: usize) -> Vec<Hash> {
+    let mut siblings = Vec::new();
+    for level in &levels[..levels.len() - 1] {
+        if let Some(sibling) = level.get(index ^ 1) {
+            siblings.push(*sibling);
+        }
+        index /= 2;
+    }
+    siblings
+}
+
+fn hex(hash: &Hash) -> String {
+    hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn to_json(root: &Hash, words: &[String], levels: &[Vec<Hash>]) -> String {
+    let proofs: Vec<String> = words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
+            let siblings: Vec<String> = proof(levels, index).iter().map(|h| format!("\"{}\"", hex(h))).collect();
+            format!("    \"{}\": [{}]", word, siblings.join(", "))
+        })
+        .collect();
+    format!(
+        "{{\n  \"root\": \"{}\",\n  \"count\": {},\n  \"proofs\": {{\n{}\n  }}\n}}\n",
+        hex(root),
+        words.len(),
+        proofs.join(",\n")
+    )
+}
+
+fn run(input: &str, output: &str, indexed: bool) -> Result<Hash, String> {
+    let text = fs::read_to_string(input).map_err(|e| format!("{}: {}", input, e))?;
+    let words = parse_words(&text).map_err(|e| format!("{}: {}", input, e))?;
+    if u32::try_from(words.len()).is_err() {
+        return Err(format!("{}: too many words", input));
+    }
+    let levels = build_levels(&words, indexed);
+    let root = levels.last().unwrap()[0];
+    fs::write(output, to_json(&root, &words, &levels)).map_err(|e| format!("{}: {}", output, e))?;
+    eprintln!("{} words written to {}", words.len(), output);
+    Ok(root)
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    let indexed = args.get(1).map(String::as_str) == Some("--indexed");
+    if indexed {
+        args.remove(1);
+    }
+    if args.len() != 3 {
+        eprintln!("usage: {} [--indexed] <words.txt> <out.json>", args[0]);
+        process::exit(2);
+    }
+    match run(&args[1], &args[2], indexed) {
+        Ok(root) => println!("{}", hex(&root)),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_and_normalizes_word_lists() {
+        let parsed = parse_words("# answers\ncrane\n\n  SLATE \nCRANE\n").unwrap();
+        assert_eq!(parsed, words(&["CRANE", "SLATE"]));
+        assert!(parse_words("CRANE\nPLOT\n").unwrap_err().contains("line 2"));
+        assert!(parse_words("CR4NE\n").is_err());
+        assert!(parse_words("# nothing\n").is_err());
+    }
+
+    #[test]
+    fn every_proof_verifies_for_every_list_size() {
+        let all = words(&["ABOUT", "BRAIN", "CHAIR", "DANCE", "EARTH", "FROST", "GHOST", "HEART", "IGLOO"]);
+        for size in 1..=all.len() {
+            let list = &all[..size];
+            let levels = build_levels(list, false);
+            let root = levels.last().unwrap()[0];
+            for (index, word) in list.iter().enumerate() {
+                assert!(merkle::verify(&sha256, &root, word.as_bytes(), &proof(&levels, index)), "{} of {}", word, size);
+            }
+        }
+    }
+
+    #[test]
+    fn proofs_do_not_verify_other_words_or_roots() {
+        let list = words(&["CRANE", "SLATE", "TRACE", "CRATE", "REACT"]);
+        let levels = build_levels(&list, false);
+        let root = levels.last().unwrap()[0];
+        let crane = proof(&levels, 0);
+
+        assert!(!merkle::verify(&sha256, &root, b"SLATE", &crane));
+        assert!(!merkle::verify(&sha256, &root, b"QQQQQ", &crane));
+        assert!(!merkle::verify(&sha256, &[0; 32], b"CRANE", &crane));
+        // An inner node is not accepted as a word
+        assert!(!merkle::verify(&sha256, &root, &levels[1][0], &proof(&levels, 0)[1..]));
+    }
+
+    #[test]
+    fn indexed_proofs_pin_words_to_their_position() {
+        let list = words(&["CRANE", "SLATE", "TRACE", "CRATE", "REACT"]);
+        let levels = build_levels(&list, true);
+        let root = levels.last().unwrap()[0];
+        for (index, word) in list.iter().enumerate() {
+            let word: [u8; WORD_LENGTH] = word.as_bytes().try_into().unwrap();
+            let proof = proof(&levels, index);
+            assert!(merkle::verify(&sha256, &root, &merkle::answer_leaf_data(index as u32, &word), &proof));
+            assert!(!merkle::verify(&sha256, &root, &merkle::answer_leaf_data(index as u32 + 1, &word), &proof));
+            assert!(!merkle::verify(&sha256, &root, &word, &proof));
+        }
+    }
+
+    #[test]
+    fn json_lists_root_and_proofs() {
+        let list = words(&["CRANE", "SLATE"]);
+        let levels = build_levels(&list, false);
+        let json = to_json(&levels[1][0], &list, &levels);
+        assert!(json.contains(&format!("\"root\": \"{}\"", hex(&levels[1][0]))));
+        assert!(json.contains("\"count\": 2"));
+        assert!(json.contains(&format!("\"CRANE\": [\"{}\"]", hex(&levels[0][1]))));
+    }
+}
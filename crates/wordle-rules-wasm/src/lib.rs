@@ -0,0 +1,80 @@
+// WebAssembly bindings for wordle-rules, so the browser, the server and the program score guesses the same way
+// Build with `wasm-pack build crates/wordle-rules-wasm --target web` (or `--target nodejs` for the server)
+use wasm_bindgen::prelude::*;
+use wordle_rules::{HardModeViolation, LetterScore, WORD_LENGTH};
+
+// Tile results use the frontend's CSS class names
+fn score_name(score: LetterScore) -> &'static str {
+    match score {
+        LetterScore::Correct => "correct",
+        LetterScore::Present => "present",
+        LetterScore::Absent => "absent",
+    }
+}
+
+// Words from JS are trimmed and uppercased, then must be exactly WORD_LENGTH letters A-Z
+fn parse_word(word: &str) -> Result<[u8; WORD_LENGTH], String> {
+    let word = word.trim().to_ascii_uppercase();
+    let bytes: [u8; WORD_LENGTH] = word
+        .as_bytes()
+        .try_into()
+        .map_err(|_| format!("Word must be exactly {} letters", WORD_LENGTH))?;
+    if !wordle_rules::is_valid_word(&bytes) {
+        return Err("Word must contain only letters A-Z".into());
+    }
+    Ok(bytes)
+}
+
+// History entries are "GUESS:pattern", the pattern using the first letter of each tile result
+// e.g. "CRANE:apaac" for absent, present, absent, absent, correct
+fn parse_history_entry(entry: &str) -> Result<([u8; WORD_LENGTH], [LetterScore; WORD_LENGTH]), String> {
+    let (guess, pattern) = entry
+        .split_once(':')
+        .ok_or_else(|| format!("History entry \"{}\" must look like GUESS:pattern", entry))?;
+    let guess = parse_word(guess)?;
+
+    let pattern = pattern.trim().as_bytes();
+    if pattern.len() != WORD_LENGTH {
+        return Err(format!("Pattern in \"{}\" must be exactly {} tiles", entry, WORD_LENGTH));
+    }
+    let mut score = [LetterScore::Absent; WORD_LENGTH];
+    for (tile, c) in score.iter_mut().zip(pattern) {
+        *tile = match c.to_ascii_lowercase() {
+            b'c' => LetterScore::Correct,
+            b'p' => LetterScore::Present,
+            b'a' => LetterScore::Absent,
+            _ => return Err(format!("Pattern in \"{}\" may only use c, p and a", entry)),
+        };
+    }
+    Ok((guess, score))
+}
+
+// Score a guess against the word: one of "correct", "present" or "absent" per tile
+#[wasm_bindgen]
+pub fn score_guess(word: &str, guess: &str) -> Result<Vec<String>, String> {
+    let word = parse_word(word)?;
+    let guess = parse_word(guess)?;
+    Ok(wordle_rules::score_guess(&guess, &word)
+        .iter()
+        .map(|s| score_name(*s).to_string())
+        .collect())
+}
+
+// Check a hard-mode guess against every earlier guess; throws a message describing the first broken rule
+#[wasm_bindgen]
+pub fn validate_hard_mode(history: Vec<String>, guess: &str) -> Result<(), String> {
+    let guess = parse_word(guess)?;
+    for entry in &history {
+        let (previous, score) = parse_history_entry(entry)?;
+        match wordle_rules::check_hard_mode(&previous, &score, &guess) {
+            Ok(()) => {}
+            Err(HardModeViolation::MissingCorrect { position, letter }) => {
+                return Err(format!("Letter {} must stay in position {}", letter as char, position + 1));
+            }
+            Err(HardModeViolation::MissingPresent { letter }) => {
+                return Err(format!("Guess must contain {}", letter as char));
+            }
+        }
+    }
+    Ok(())
+}
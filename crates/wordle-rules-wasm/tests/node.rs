@@ -0,0 +1,77 @@
+// Run headlessly under Node with `wasm-pack test --node crates/wordle-rules-wasm`
+// (plain `cargo test` runs the same tests natively)
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test;
+use wordle_rules_wasm::{score_guess, validate_hard_mode};
+
+fn tiles(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+fn history(entries: &[&str]) -> Vec<String> {
+    entries.iter().map(|e| e.to_string()).collect()
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn scores_with_css_class_names() {
+    assert_eq!(
+        score_guess("CRANE", "CRATE"),
+        Ok(tiles(&["correct", "correct", "correct", "absent", "correct"]))
+    );
+    assert_eq!(
+        score_guess("CRANE", "EERIE"),
+        Ok(tiles(&["absent", "absent", "present", "absent", "correct"]))
+    );
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn accepts_lowercase_and_whitespace() {
+    assert_eq!(score_guess(" crane", "Crane "), score_guess("CRANE", "CRANE"));
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn rejects_bad_words() {
+    assert_eq!(score_guess("CRANE", "CRAN"), Err("Word must be exactly 5 letters".to_string()));
+    assert_eq!(score_guess("CRANES", "CRANE"), Err("Word must be exactly 5 letters".to_string()));
+    assert_eq!(score_guess("CRANE", "CR4NE"), Err("Word must contain only letters A-Z".to_string()));
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn hard_mode_with_no_history_accepts_anything() {
+    assert_eq!(validate_hard_mode(Vec::new(), "BUMPY"), Ok(()));
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn hard_mode_enforces_greens_and_yellows() {
+    // CRANE against THREW: R and E present
+    let h = history(&["CRANE:apaap"]);
+    assert_eq!(validate_hard_mode(h.clone(), "TOWER"), Ok(()));
+    assert_eq!(validate_hard_mode(h, "TOWNS"), Err("Guess must contain R".to_string()));
+
+    // CRANE against CRATE: C, R, A and E correct
+    let h = history(&["CRANE:cccac"]);
+    assert_eq!(validate_hard_mode(h.clone(), "CRATE"), Ok(()));
+    assert_eq!(validate_hard_mode(h, "CRAET"), Err("Letter E must stay in position 5".to_string()));
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn hard_mode_checks_every_earlier_guess() {
+    // Answer THEME: CRANE reveals a green E, then EERIE reveals a second E
+    let h = history(&["CRANE:aaaac", "EERIE:paaac"]);
+    assert_eq!(validate_hard_mode(h.clone(), "THEME"), Ok(()));
+    assert_eq!(validate_hard_mode(h, "SHAKE"), Err("Guess must contain E".to_string()));
+}
+
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+fn hard_mode_rejects_malformed_history() {
+    assert!(validate_hard_mode(history(&["CRANE"]), "CRATE").is_err());
+    assert!(validate_hard_mode(history(&["CRANE:ccc"]), "CRATE").is_err());
+    assert!(validate_hard_mode(history(&["CRANE:cccxc"]), "CRATE").is_err());
+}
@@ -0,0 +1,289 @@
+// Wordle rules shared by the escrow program and off-chain tools
+// Words are uppercase ASCII; scoring works for any word length via const generics
+#![no_std]
+
+pub mod merkle;
+pub mod seed;
+
+// Standard game: 5-letter words, 6 guesses per player
+pub const WORD_LENGTH: usize = 5;
+pub const MAX_GUESSES: usize = 6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LetterScore {
+    Absent,  // gray: letter not in the answer (or all copies already accounted for)
+    Present, // yellow: letter in the answer at another position
+    Correct, // green: letter in the right position
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HardModeViolation {
+    // A green letter from the previous guess was moved or dropped
+    MissingCorrect { position: usize, letter: u8 },
+    // A revealed letter was not reused as many times as it was revealed
+    MissingPresent { letter: u8 },
+}
+
+// Words must be non-empty and made of uppercase A-Z only
+pub fn is_valid_word(word: &[u8]) -> bool {
+    !word.is_empty() && word.iter().all(u8::is_ascii_uppercase)
+}
+
+// Score a guess against the answer
+// Greens are assigned first; yellows then use up the remaining copies of each letter left to right,
+// so a repeated guess letter is only yellow as many times as the answer has unmatched copies
+pub fn score_guess<const N: usize>(guess: &[u8; N], answer: &[u8; N]) -> [LetterScore; N] {
+    let mut score = [LetterScore::Absent; N];
+    let mut unmatched = [0u8; 256];
+
+    for i in 0..N {
+        if guess[i] == answer[i] {
+            score[i] = LetterScore::Correct;
+        } else {
+            unmatched[answer[i] as usize] += 1;
+        }
+    }
+
+    for i in 0..N {
+        let remaining = &mut unmatched[guess[i] as usize];
+        if score[i] != LetterScore::Correct && *remaining > 0 {
+            score[i] = LetterScore::Present;
+            *remaining -= 1;
+        }
+    }
+
+    score
+}
+
+// A guess is solved when every letter is green
+pub fn is_solved(score: &[LetterScore]) -> bool {
+    score.iter().all(|s| *s == LetterScore::Correct)
+}
+
+// Hard mode: greens must stay in place and every revealed letter (green or yellow) must be reused,
+// at least as many times as it was revealed in the previous guess
+pub fn check_hard_mode<const N: usize>(
+    previous: &[u8; N],
+    score: &[LetterScore; N],
+    guess: &[u8; N],
+) -> Result<(), HardModeViolation> {
+    for i in 0..N {
+        if score[i] == LetterScore::Correct && guess[i] != previous[i] {
+            return Err(HardModeViolation::MissingCorrect { position: i, letter: previous[i] });
+        }
+    }
+
+    for i in 0..N {
+        if score[i] == LetterScore::Absent {
+            continue;
+        }
+        let letter = previous[i];
+        let revealed = (0..N).filter(|&j| previous[j] == letter && score[j] != LetterScore::Absent).count();
+        let reused = guess.iter().filter(|&&c| c == letter).count();
+        if reused < revealed {
+            return Err(HardModeViolation::MissingPresent { letter });
+        }
+    }
+
+    Ok(())
+}
+
+// Number of guesses still available to a player who has made `used` guesses
+pub fn guesses_remaining(used: usize) -> usize {
+    MAX_GUESSES.saturating_sub(used)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LetterScore::{Absent as A, Correct as C, Present as P};
+    use super::*;
+
+    fn score(guess: &str, answer: &str) -> [LetterScore; WORD_LENGTH] {
+        let guess: [u8; WORD_LENGTH] = guess.as_bytes().try_into().unwrap();
+        let answer: [u8; WORD_LENGTH] = answer.as_bytes().try_into().unwrap();
+        score_guess(&guess, &answer)
+    }
+
+    fn hard(previous: &str, answer: &str, guess: &str) -> Result<(), HardModeViolation> {
+        let previous: [u8; WORD_LENGTH] = previous.as_bytes().try_into().unwrap();
+        let answer: [u8; WORD_LENGTH] = answer.as_bytes().try_into().unwrap();
+        let guess: [u8; WORD_LENGTH] = guess.as_bytes().try_into().unwrap();
+        check_hard_mode(&previous, &score_guess(&previous, &answer), &guess)
+    }
+
+    // Straightforward reference: for each letter, count greens, then hand out yellows left to right
+    fn reference_score<const N: usize>(guess: &[u8; N], answer: &[u8; N]) -> [LetterScore; N] {
+        let mut score = [A; N];
+        for i in 0..N {
+            if guess[i] == answer[i] {
+                score[i] = C;
+            }
+        }
+        for i in 0..N {
+            if score[i] == C {
+                continue;
+            }
+            let letter = guess[i];
+            let in_answer = answer.iter().filter(|&&c| c == letter).count();
+            let greens = (0..N).filter(|&j| guess[j] == letter && score[j] == C).count();
+            let earlier_yellows = (0..i).filter(|&j| guess[j] == letter && score[j] == P).count();
+            if greens + earlier_yellows < in_answer {
+                score[i] = P;
+            }
+        }
+        score
+    }
+
+    #[test]
+    fn exact_match_is_solved() {
+        assert_eq!(score("CRANE", "CRANE"), [C; 5]);
+        assert!(is_solved(&score("CRANE", "CRANE")));
+        assert!(!is_solved(&score("CRANE", "CRATE")));
+    }
+
+    #[test]
+    fn no_common_letters_is_all_gray() {
+        assert_eq!(score("BUMPY", "CRANE"), [A; 5]);
+    }
+
+    #[test]
+    fn anagram_is_all_yellow() {
+        assert_eq!(score("LEAST", "STEAL"), [P; 5]);
+    }
+
+    #[test]
+    fn repeated_guess_letter_with_single_copy_in_answer() {
+        // Only the first E is yellow; the second has no copy left
+        assert_eq!(score("EERIE", "CRANE"), [A, A, P, A, C]);
+        assert_eq!(score("GEESE", "THREW"), [A, P, A, A, A]);
+    }
+
+    #[test]
+    fn green_takes_priority_over_earlier_yellow() {
+        // The green E at the end uses up the only E, so the first E is gray
+        assert_eq!(score("EPEEE", "CRANE"), [A, A, A, A, C]);
+        assert_eq!(score("SPEED", "ABIDE"), [A, A, P, A, P]);
+        assert_eq!(score("ERASE", "THOSE"), [A, A, A, C, C]);
+    }
+
+    #[test]
+    fn repeated_letter_in_answer_and_guess() {
+        // Answer has two Ls; both guessed, one in place
+        assert_eq!(score("LLAMA", "HELLO"), [P, P, A, A, A]);
+        assert_eq!(score("HELLO", "LLAMA"), [A, A, P, P, A]);
+        assert_eq!(score("SKILL", "STILL"), [C, A, C, C, C]);
+        assert_eq!(score("ALLOT", "LLAMA"), [P, C, P, A, A]);
+    }
+
+    #[test]
+    fn more_guess_copies_than_answer_copies() {
+        // Answer has two Es, guess has three: greens first, then one yellow, rest gray
+        assert_eq!(score("EEEXX", "XEXEX"), [P, C, A, P, C]);
+        assert_eq!(score("EEEEE", "EXEXX"), [C, A, C, A, A]);
+    }
+
+    #[test]
+    fn fewer_guess_copies_than_answer_copies() {
+        assert_eq!(score("EXXXX", "XEEEX"), [P, P, A, A, C]);
+        assert_eq!(score("ABBEY", "KEBAB"), [P, P, C, P, A]);
+        assert_eq!(score("BABES", "ABBEY"), [P, P, C, C, A]);
+    }
+
+    #[test]
+    fn all_same_letter_guess() {
+        assert_eq!(score("AAAAA", "LLAMA"), [A, A, C, A, C]);
+        assert_eq!(score("AAAAA", "BBBBB"), [A; 5]);
+        assert_eq!(score("SSSSS", "SASSY"), [C, A, C, C, A]);
+    }
+
+    #[test]
+    fn matches_reference_for_every_pair_over_small_alphabet() {
+        // Every pair of 4-letter words over {A, B, C}: 81 x 81 combinations, all duplicate patterns
+        const LETTERS: [u8; 3] = [b'A', b'B', b'C'];
+        let word = |mut n: usize| {
+            let mut w = [0u8; 4];
+            for c in w.iter_mut() {
+                *c = LETTERS[n % 3];
+                n /= 3;
+            }
+            w
+        };
+        for g in 0..81 {
+            for a in 0..81 {
+                let (guess, answer) = (word(g), word(a));
+                let scored = score_guess(&guess, &answer);
+                assert_eq!(scored, reference_score(&guess, &answer), "{:?} vs {:?}", guess, answer);
+
+                // Letter counts are conserved: greens + yellows never exceed the answer's copies
+                for letter in LETTERS {
+                    let hits = (0..4).filter(|&i| guess[i] == letter && scored[i] != A).count();
+                    let in_guess = guess.iter().filter(|&&c| c == letter).count();
+                    let in_answer = answer.iter().filter(|&&c| c == letter).count();
+                    assert_eq!(hits, in_guess.min(in_answer));
+                }
+                assert_eq!(is_solved(&scored), guess == answer);
+            }
+        }
+    }
+
+    #[test]
+    fn other_word_lengths() {
+        assert_eq!(score_guess(b"TREES", b"STEER"), [P, P, C, C, P]);
+        assert_eq!(score_guess(b"ABBA", b"BABA"), [P, P, C, C]);
+        assert_eq!(score_guess(b"LETTERS", b"SETTLED"), [P, C, C, C, P, A, P]);
+        assert_eq!(score_guess(b"A", b"A"), [C]);
+    }
+
+    #[test]
+    fn valid_words() {
+        assert!(is_valid_word(b"CRANE"));
+        assert!(is_valid_word(b"AB"));
+        assert!(!is_valid_word(b""));
+        assert!(!is_valid_word(b"crane"));
+        assert!(!is_valid_word(b"CR4NE"));
+        assert!(!is_valid_word(b"CRAN\xC9"));
+    }
+
+    #[test]
+    fn hard_mode_keeps_greens_in_place() {
+        assert_eq!(hard("CRANE", "CRATE", "CRATE"), Ok(()));
+        assert_eq!(
+            hard("CRANE", "CRATE", "RCATE"),
+            Err(HardModeViolation::MissingCorrect { position: 0, letter: b'C' })
+        );
+    }
+
+    #[test]
+    fn hard_mode_reuses_yellows_anywhere() {
+        // R and E are yellow against THREW; they may move
+        assert_eq!(hard("CRANE", "THREW", "RESET"), Ok(()));
+        assert_eq!(hard("CRANE", "THREW", "TOWER"), Ok(()));
+        assert_eq!(hard("CRANE", "THREW", "TOWNS"), Err(HardModeViolation::MissingPresent { letter: b'R' }));
+    }
+
+    #[test]
+    fn hard_mode_counts_duplicate_reveals() {
+        // Two Ls revealed against HELLO: both must be reused
+        assert_eq!(hard("LLAMA", "HELLO", "HOLLY"), Ok(()));
+        assert_eq!(hard("LLAMA", "HELLO", "LOBBY"), Err(HardModeViolation::MissingPresent { letter: b'L' }));
+        // Only one E revealed (the other is gray), so one E is enough
+        assert_eq!(hard("EERIE", "CRANE", "BRAKE"), Ok(()));
+        // Two Es revealed (green and yellow) against THESE: a single E is not enough
+        assert_eq!(hard("EERIE", "THESE", "SHAKE"), Err(HardModeViolation::MissingPresent { letter: b'E' }));
+        assert_eq!(hard("EERIE", "THESE", "THEME"), Ok(()));
+    }
+
+    #[test]
+    fn hard_mode_ignores_grays() {
+        assert_eq!(hard("BUMPY", "CRANE", "BUMPY"), Ok(()));
+        assert_eq!(hard("BUMPY", "CRANE", "CRANE"), Ok(()));
+    }
+
+    #[test]
+    fn guess_limit() {
+        assert_eq!(guesses_remaining(0), MAX_GUESSES);
+        assert_eq!(guesses_remaining(5), 1);
+        assert_eq!(guesses_remaining(6), 0);
+        assert_eq!(guesses_remaining(9), 0);
+    }
+}
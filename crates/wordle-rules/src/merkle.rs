@@ -0,0 +1,34 @@
+// Merkle proofs over word lists, so the program can check a word is on a list by its root alone
+// Leaves are hash(0x00 || word) and nodes hash(0x01 || lo || hi) with the pair sorted, so a proof is
+// just the sibling hashes from leaf to root; a node without a sibling moves up a level unchanged
+// The hash is passed in (sha256 on both sides) so the program can use the runtime's syscall
+
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub fn leaf_hash<H: Fn(&[&[u8]]) -> Hash>(hash: &H, word: &[u8]) -> Hash {
+    hash(&[LEAF_PREFIX, word])
+}
+
+pub fn node_hash<H: Fn(&[&[u8]]) -> Hash>(hash: &H, a: &Hash, b: &Hash) -> Hash {
+    let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+    hash(&[NODE_PREFIX, lo, hi])
+}
+
+// True when `proof` links `word` to `root`
+pub fn verify<H: Fn(&[&[u8]]) -> Hash>(hash: &H, root: &Hash, word: &[u8], proof: &[Hash]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf_hash(hash, word), |acc, sibling| node_hash(hash, &acc, sibling));
+    computed == *root
+}
+
+// Answer list leaves commit to each word's position too, so a proof pins the word to a derived index
+pub fn answer_leaf_data(index: u32, word: &[u8; crate::WORD_LENGTH]) -> [u8; 4 + crate::WORD_LENGTH] {
+    let mut data = [0u8; 4 + crate::WORD_LENGTH];
+    data[..4].copy_from_slice(&index.to_le_bytes());
+    data[4..].copy_from_slice(word);
+    data
+}
@@ -0,0 +1,97 @@
+// Provably fair answer selection: each player commits to hash(seed) when creating or joining, the program
+// records the latest slot hash at join, and the answer index is derived from all of them once both seeds
+// are revealed. Anyone can re-run this off-chain from the game account and the revealed seeds.
+
+use crate::merkle::Hash;
+
+pub fn answer_index<H: Fn(&[&[u8]]) -> Hash>(
+    hash: &H,
+    seeds: [&Hash; 2],
+    slot_hash: &Hash,
+    answer_root: &Hash,
+    answer_count: u32,
+) -> u32 {
+    let digest = hash(&[seeds[0], seeds[1], slot_hash, answer_root]);
+    let mut head = [0u8; 8];
+    head.copy_from_slice(&digest[..8]);
+    (u64::from_le_bytes(head) % answer_count as u64) as u32
+}
+
+// Tiebreak rounds re-derive the answer from the same seeds with the round mixed into the slot hash
+// Round 0 uses the slot hash as is
+pub fn round_slot_hash<H: Fn(&[&[u8]]) -> Hash>(hash: &H, slot_hash: &Hash, round: u8) -> Hash {
+    if round == 0 {
+        *slot_hash
+    } else {
+        hash(&[slot_hash, &[round]])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    // The same vectors are checked against the JS mirror in test/answer-index.test.js
+    const SEEDS: [Hash; 2] = [[1; 32], [2; 32]];
+    const SLOT_HASH: Hash = [3; 32];
+    const ANSWER_ROOT: Hash = [4; 32];
+
+    fn sha256(parts: &[&[u8]]) -> Hash {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().into()
+    }
+
+    fn hex(hash: &Hash) -> std::string::String {
+        hash.iter().map(|b| std::format!("{:02x}", b)).collect()
+    }
+
+    fn index(round: u8, answer_count: u32) -> u32 {
+        let slot_hash = round_slot_hash(&sha256, &SLOT_HASH, round);
+        answer_index(&sha256, [&SEEDS[0], &SEEDS[1]], &slot_hash, &ANSWER_ROOT, answer_count)
+    }
+
+    #[test]
+    fn round_zero_keeps_the_slot_hash() {
+        assert_eq!(round_slot_hash(&sha256, &SLOT_HASH, 0), SLOT_HASH);
+    }
+
+    #[test]
+    fn later_rounds_hash_the_round_in() {
+        assert_eq!(
+            hex(&round_slot_hash(&sha256, &SLOT_HASH, 1)),
+            "339721a750dca8362d3064a5314836c429217be4d6957c7800b5d8452be608e8"
+        );
+        assert_eq!(
+            hex(&round_slot_hash(&sha256, &SLOT_HASH, 2)),
+            "12a7b248579deb04f68dac6d3db20efde09da0b3bb994f0c45bcfe086044edb9"
+        );
+        assert_eq!(
+            hex(&round_slot_hash(&sha256, &SLOT_HASH, 255)),
+            "f90fbcef1abe97aa0bc464b6fe0ffc44b221dc026c3c7a5379bd685c8199e68e"
+        );
+    }
+
+    #[test]
+    fn answer_index_vectors() {
+        assert_eq!(index(0, 536), 438);
+        assert_eq!(index(1, 536), 245);
+        assert_eq!(index(2, 536), 166);
+        assert_eq!(index(255, 536), 29);
+        assert_eq!(index(0, u32::MAX), 219483253);
+        assert_eq!(index(1, u32::MAX), 3591510782);
+        assert_eq!(index(0, 1), 0);
+    }
+
+    #[test]
+    fn seed_order_matters() {
+        let slot_hash = round_slot_hash(&sha256, &SLOT_HASH, 0);
+        let swapped = answer_index(&sha256, [&SEEDS[1], &SEEDS[0]], &slot_hash, &ANSWER_ROOT, u32::MAX);
+        assert_ne!(swapped, index(0, u32::MAX));
+    }
+}
@@ -1,201 +1,661 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::slot_hashes;
 use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeAmount;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+use wordle_rules::merkle;
 
 declare_id!("2E9mCNwZ2LLHjFpFQUC8K23ARHwhUEoMGq9yZpKWu7VM");
 
-// House/Dev wallet for fees
-pub const HOUSE_WALLET: &str = "FRG1E6NiJ9UVN4T4v2r9hN1JzqB9r1uPuetCLXuqiRjT";
+// Fees are configured in basis points (100 = 1%)
+pub const MAX_FEE_BPS: u64 = 10_000;
 
-// Fee percentages (in basis points: 100 = 1%)
-pub const WINNER_FEE_BPS: u64 = 200; // 2%
-pub const FORFEIT_FEE_BPS: u64 = 500; // 5%
+// Secret words are 5 uppercase ASCII letters; each player gets 6 guesses, same as the server
+// Spelled out as literals so the IDL generator can size arrays; checked against wordle-rules below
+pub const WORD_LENGTH: usize = 5;
+pub const MAX_GUESSES_PER_PLAYER: usize = 6;
+const _: () = assert!(WORD_LENGTH == wordle_rules::WORD_LENGTH && MAX_GUESSES_PER_PLAYER == wordle_rules::MAX_GUESSES);
+
+// Upper bound on the configurable number of sudden-death tiebreak rounds
+pub const MAX_TIEBREAK_ROUNDS: u8 = 5;
+
+// PDA seeds
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const GAME_SEED: &[u8] = b"game"; // ["game", creator, nonce]
+pub const GUESS_LOG_SEED: &[u8] = b"guess_log"; // ["guess_log", game]
+pub const MINT_CONFIG_SEED: &[u8] = b"mint_config"; // ["mint_config", mint]
 
 #[program]
 pub mod wordle_escrow {
     use super::*;
 
-    // Create a new game escrow
-    pub fn create_game(ctx: Context<CreateGame>, wager_amount: u64) -> Result<()> {
+    // Initialize program config - only the program's upgrade authority can do this
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        params.validate()?;
+        
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.bump = ctx.bumps.config;
+        config.apply(&params);
+        
+        msg!("Config initialized. Admin: {}, Settlement authority: {}", config.admin, config.settlement_authority);
+        Ok(())
+    }
+
+    // Update fees, minimum wager, house wallet and settlement authority (admin only)
+    // Games already created keep the fees recorded when they were created
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        params.validate()?;
+        
+        let config = &mut ctx.accounts.config;
+        config.apply(&params);
+        
+        msg!("Config updated. Winner fee: {} bps, Forfeit fee: {} bps, Min wager: {} lamports", config.winner_fee_bps, config.forfeit_fee_bps, config.min_wager);
+        Ok(())
+    }
+
+    // Allow wagers in an SPL token and set its minimum wager in base units (admin only)
+    // Token games can only be created for mints configured here
+    pub fn set_mint_config(ctx: Context<SetMintConfig>, min_wager: u64) -> Result<()> {
+        require!(min_wager > 0, ErrorCode::InvalidWager);
+        
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.mint = ctx.accounts.mint.key();
+        mint_config.min_wager = min_wager;
+        mint_config.bump = ctx.bumps.mint_config;
+        
+        msg!("Mint {} configured. Min wager: {}", mint_config.mint, min_wager);
+        Ok(())
+    }
+
+    // Create a new game - the game PDA itself holds the staked lamports
+    // Passing a mint (with its mint config, the creator's token account and the vault) makes it a token game,
+    // escrowed in the game PDA's associated token account instead
+    // The nonce lets one creator have several games; clients pick any unused value
+    // An optional join deadline (unix timestamp) lets anyone expire the game if nobody joins in time
+    // `seed_commitment` is sha256 of the creator's secret seed, revealed later to derive the answer
+    // `both_lost_policy` decides what happens to the pot if neither player solves the word
+    pub fn create_game(
+        ctx: Context<CreateGame>,
+        nonce: u64,
+        wager_amount: u64,
+        join_deadline: Option<i64>,
+        seed_commitment: [u8; 32],
+        both_lost_policy: BothLostPolicy,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
         let game_account = &mut ctx.accounts.game_account;
-        let escrow_account = &mut ctx.accounts.escrow_account;
+        let now = Clock::get()?.unix_timestamp;
+        
+        // Token games use the mint's own minimum wager instead of the lamport one
+        let (mint, min_wager) = match (&ctx.accounts.mint, &ctx.accounts.mint_config) {
+            (None, _) => (None, config.min_wager),
+            (Some(mint), Some(mint_config)) => {
+                require_keys_eq!(mint_config.mint, mint.key(), ErrorCode::MintNotConfigured);
+                (Some(mint.key()), mint_config.min_wager)
+            }
+            (Some(_), None) => return err!(ErrorCode::MintNotConfigured),
+        };
         
         // Validate wager amount
         require!(wager_amount > 0, ErrorCode::InvalidWager);
-        require!(wager_amount >= 22_000_000, ErrorCode::WagerTooLow); // 0.022 SOL minimum
+        require!(wager_amount >= min_wager, ErrorCode::WagerTooLow);
+        if let Some(deadline) = join_deadline {
+            require!(deadline > now, ErrorCode::InvalidJoinDeadline);
+        }
         
         // Initialize game state
         game_account.creator = ctx.accounts.creator.key();
+        game_account.nonce = nonce;
+        game_account.bump = ctx.bumps.game_account;
         game_account.wager_amount = wager_amount;
+        game_account.mint = mint;
         game_account.status = GameStatus::Waiting;
         game_account.players = [ctx.accounts.creator.key(), Pubkey::default()];
         game_account.winner = Pubkey::default();
-        game_account.created_at = Clock::get()?.unix_timestamp;
-        
-        // Initialize escrow account
-        escrow_account.game = ctx.accounts.game_account.key();
-        escrow_account.total_amount = wager_amount;
-        escrow_account.creator_deposited = wager_amount;
-        escrow_account.opponent_deposited = 0;
-        escrow_account.created_at = Clock::get()?.unix_timestamp;
-        
-        // Transfer SOL from creator to escrow
-        let transfer_instruction = system_program::Transfer {
-            from: ctx.accounts.creator.to_account_info(),
-            to: ctx.accounts.escrow_account.to_account_info(),
-        };
+        game_account.draw_offers = [false, false];
+        game_account.both_lost_policy = both_lost_policy;
+        game_account.round = 0;
+        game_account.max_tiebreak_rounds = config.max_tiebreak_rounds;
+        game_account.tiebreak_fallback = config.tiebreak_fallback;
+        game_account.created_at = now;
+        game_account.join_deadline = join_deadline;
         
-        let cpi_context = CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            transfer_instruction,
-        );
+        // Lock in the fees for the lifetime of this game
+        game_account.winner_fee_bps = config.winner_fee_bps;
+        game_account.forfeit_fee_bps = config.forfeit_fee_bps;
+        game_account.draw_fee_bps = config.draw_fee_bps;
+        game_account.max_game_duration = config.max_game_duration;
+        game_account.round_time_limit = config.round_time_limit;
+        game_account.crank_reward = config.crank_reward;
+        game_account.guess_root = config.guess_root;
+        game_account.answer_root = config.answer_root;
+        game_account.answer_count = config.answer_count;
+        
+        // The answer is derived from both players' seeds once revealed
+        game_account.seed_commitments = [seed_commitment, [0; 32]];
+        game_account.seeds = [None, None];
+        game_account.answer_index = None;
+        
+        // Transfer the wager from creator to the game account (or its vault)
+        let pot = Pot::new(game_account, &ctx.accounts.mint, &ctx.accounts.vault, &ctx.accounts.token_program)?;
+        let deposited = pot.deposit(
+            game_account,
+            &ctx.accounts.creator,
+            ctx.accounts.creator_token_account.as_ref(),
+            &ctx.accounts.system_program,
+            wager_amount,
+        )?;
         
-        system_program::transfer(cpi_context, wager_amount)?;
+        // Wager bookkeeping uses what arrived, which is less than the wager for mints with transfer fees
+        game_account.total_amount = deposited;
+        game_account.creator_deposited = deposited;
+        game_account.opponent_deposited = 0;
         
-        msg!("Game created with wager: {} lamports", wager_amount);
+        emit!(GameCreated {
+            game: ctx.accounts.game_account.key(),
+            creator: ctx.accounts.creator.key(),
+            nonce,
+            mint,
+            wager_amount,
+            join_deadline,
+            seed_commitment,
+            both_lost_policy,
+            created_at: now,
+        });
+        
+        msg!("Game created with wager: {} {}", wager_amount, if mint.is_some() { "tokens" } else { "lamports" });
         Ok(())
     }
 
     // Join an existing game
-    pub fn join_game(ctx: Context<JoinGame>) -> Result<()> {
+    // `seed_commitment` is sha256 of the opponent's secret seed; the latest slot hash is recorded alongside
+    pub fn join_game(ctx: Context<JoinGame>, seed_commitment: [u8; 32]) -> Result<()> {
         let game_account = &mut ctx.accounts.game_account;
-        let escrow_account = &mut ctx.accounts.escrow_account;
+        let now = Clock::get()?.unix_timestamp;
         
         require!(game_account.status == GameStatus::Waiting, ErrorCode::GameNotWaiting);
         require!(game_account.players[1] == Pubkey::default(), ErrorCode::GameFull);
         require!(ctx.accounts.opponent.key() != game_account.creator, ErrorCode::CannotJoinOwnGame);
+        if let Some(deadline) = game_account.join_deadline {
+            require!(now <= deadline, ErrorCode::JoinDeadlinePassed);
+        }
         
         // Add player to game
         game_account.players[1] = ctx.accounts.opponent.key();
         game_account.status = GameStatus::Playing;
-        game_account.started_at = Clock::get()?.unix_timestamp;
+        game_account.started_at = now;
         
-        // Update escrow
-        escrow_account.opponent_deposited = game_account.wager_amount;
-        escrow_account.total_amount = game_account.wager_amount * 2;
+        // Both seeds are now committed; the slot hash adds entropy neither player knew when committing
+        let (seed_slot, seed_slot_hash) = latest_slot_hash(&ctx.accounts.slot_hashes)?;
+        game_account.seed_commitments[1] = seed_commitment;
+        game_account.seed_slot = seed_slot;
+        game_account.seed_slot_hash = seed_slot_hash;
         
-        // Transfer SOL from opponent to escrow
-        let transfer_instruction = system_program::Transfer {
-            from: ctx.accounts.opponent.to_account_info(),
-            to: ctx.accounts.escrow_account.to_account_info(),
-        };
+        // Open the guess log for this game (rent paid by the opponent)
+        let guess_log = &mut ctx.accounts.guess_log;
+        guess_log.game = game_account.key();
+        guess_log.players = game_account.players;
+        guess_log.delegates = [None, None];
+        guess_log.guesses = Vec::new();
+        guess_log.bump = ctx.bumps.guess_log;
         
-        let cpi_context = CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            transfer_instruction,
-        );
+        // Transfer the matching wager from opponent to the game account (or its vault)
+        let pot = Pot::new(game_account, &ctx.accounts.mint, &ctx.accounts.vault, &ctx.accounts.token_program)?;
+        let deposited = pot.deposit(
+            game_account,
+            &ctx.accounts.opponent,
+            ctx.accounts.opponent_token_account.as_ref(),
+            &ctx.accounts.system_program,
+            game_account.wager_amount,
+        )?;
         
-        system_program::transfer(cpi_context, game_account.wager_amount)?;
+        // Update wager bookkeeping with what actually arrived
+        game_account.opponent_deposited = deposited;
+        game_account.total_amount = game_account.creator_deposited + deposited;
+        
+        emit!(GameJoined {
+            game: game_account.key(),
+            opponent: ctx.accounts.opponent.key(),
+            total_amount: game_account.total_amount,
+            seed_commitment,
+            seed_slot,
+            seed_slot_hash,
+            started_at: now,
+        });
         
         msg!("Player joined game: {}", ctx.accounts.opponent.key());
         Ok(())
     }
 
-    // Settle game - distribute winnings with fees
-    pub fn settle_game(ctx: Context<SettleGame>, winner: Pubkey, is_forfeit: bool, both_lost: bool) -> Result<()> {
+    // Let a player hand guess submission to another key, e.g. the game server (player only)
+    pub fn set_guess_delegate(ctx: Context<SetGuessDelegate>, delegate: Option<Pubkey>) -> Result<()> {
+        let guess_log = &mut ctx.accounts.guess_log;
+        let index = guess_log.player_index(&ctx.accounts.player.key())?;
+        
+        guess_log.delegates[index] = delegate;
+        
+        msg!("Guess delegate for {} set to {:?}", ctx.accounts.player.key(), delegate);
+        Ok(())
+    }
+
+    // Append a guess to the on-chain log (player or their delegate)
+    // `proof` shows the guess is on the game's guess list
+    pub fn submit_guess(
+        ctx: Context<SubmitGuess>,
+        player: Pubkey,
+        guess: [u8; WORD_LENGTH],
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let guess_log = &mut ctx.accounts.guess_log;
+        let signer = ctx.accounts.signer.key();
+        
+        require!(ctx.accounts.game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
+        // Guesses only count against a committed word (commit_word refuses once any are logged)
+        require!(ctx.accounts.game_account.word_commitment.is_some(), ErrorCode::WordNotCommitted);
+        let clock = Clock::get()?;
+        require!(!ctx.accounts.game_account.round_over(clock.unix_timestamp), ErrorCode::RoundOver);
+        let index = guess_log.player_index(&player)?;
+        require!(
+            signer == player || guess_log.delegates[index] == Some(signer),
+            ErrorCode::UnauthorizedGuess
+        );
+        require!(wordle_rules::is_valid_word(&guess), ErrorCode::InvalidWord);
+        require!(
+            merkle::verify(&sha256, &ctx.accounts.game_account.guess_root, &guess, &proof),
+            ErrorCode::NotInWordList
+        );
+        require!(guess_log.guess_count(index) < MAX_GUESSES_PER_PLAYER, ErrorCode::OutOfGuesses);
+        
+        let slot = clock.slot;
+        guess_log.guesses.push(GuessEntry {
+            player: index as u8,
+            word: guess,
+            slot,
+        });
+        
+        emit!(GuessSubmitted {
+            game: guess_log.game,
+            player,
+            guess,
+            guess_number: guess_log.guess_count(index) as u8,
+            slot,
+        });
+        
+        msg!("Guess {} submitted for {}", guess_log.guess_count(index), player);
+        Ok(())
+    }
+
+    // Close a guess log once its game account is gone, returning rent to the opponent who paid it
+    // Every instruction that ends a started game closes the log itself; this recovers logs left behind
+    // by earlier versions that did not
+    pub fn close_guess_log(ctx: Context<CloseGuessLog>) -> Result<()> {
+        require!(ctx.accounts.game_account.data_is_empty(), ErrorCode::GameStillOpen);
+        
+        msg!("Guess log closed for game: {}", ctx.accounts.game_account.key());
+        Ok(())
+    }
+
+    // Reveal a player's seed (anyone holding it can); once both are in, the answer index is fixed
+    // Seeds are normally revealed by the server right before settlement so the word stays secret during play
+    // Sudden-death games before their last round also derive tiebreak answers from the seeds, so there only
+    // the settlement authority can reveal them, as part of settling
+    pub fn reveal_seed(ctx: Context<RevealSeed>, seed: [u8; 32]) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        
+        require!(game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
+        if game_account.both_lost_policy == BothLostPolicy::SuddenDeath
+            && game_account.round < game_account.max_tiebreak_rounds
+        {
+            require_keys_eq!(
+                ctx.accounts.revealer.key(),
+                ctx.accounts.config.settlement_authority,
+                ErrorCode::SeedsLocked
+            );
+        }
+        
+        let commitment = sha256(&[&seed]);
+        let index = (0..2)
+            .find(|&i| game_account.seed_commitments[i] == commitment && game_account.seeds[i].is_none())
+            .ok_or(ErrorCode::InvalidSeed)?;
+        game_account.seeds[index] = Some(seed);
+        
+        if let [Some(creator_seed), Some(opponent_seed)] = game_account.seeds {
+            let answer_index = wordle_rules::seed::answer_index(
+                &sha256,
+                [&creator_seed, &opponent_seed],
+                &wordle_rules::seed::round_slot_hash(&sha256, &game_account.seed_slot_hash, game_account.round),
+                &game_account.answer_root,
+                game_account.answer_count,
+            );
+            game_account.answer_index = Some(answer_index);
+            
+            emit!(AnswerDerived {
+                game: game_account.key(),
+                seeds: [creator_seed, opponent_seed],
+                seed_slot: game_account.seed_slot,
+                seed_slot_hash: game_account.seed_slot_hash,
+                round: game_account.round,
+                answer_index,
+            });
+        }
+        
+        msg!("Seed revealed for player {} of game {}", index, game_account.key());
+        Ok(())
+    }
+
+    // Commit to the secret word once play starts: sha256(word || salt) (settlement authority only)
+    pub fn commit_word(ctx: Context<CommitWord>, commitment: [u8; 32]) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        
+        require!(game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
+        require!(game_account.word_commitment.is_none(), ErrorCode::WordAlreadyCommitted);
+        // Guesses made before the commitment could have been scored against any word
+        require!(ctx.accounts.guess_log.guesses.is_empty(), ErrorCode::GuessesAlreadyLogged);
+        
+        game_account.word_commitment = Some(commitment);
+        
+        msg!("Word committed for game {}", game_account.key());
+        Ok(())
+    }
+
+    // Settle a round that ran out of time without a solve (settlement authority only)
+    // Both players lose, so the pot goes by the game's both-lost policy. Only allowed once the game's round_time_limit
+    // has passed and no logged guess solves the revealed word; guess-decided games use settle_verified
+    // The committed word is revealed here so anyone can audit which word was played
+    // The game account and guess log are closed afterwards
+    pub fn settle_game(
+        ctx: Context<SettleGame>,
+        word: [u8; WORD_LENGTH],
+        salt: [u8; 32],
+        answer_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
         let game_account = &mut ctx.accounts.game_account;
-        let escrow_account = &mut ctx.accounts.escrow_account;
+        let now = Clock::get()?.unix_timestamp;
         
         require!(game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
+        require!(game_account.round_over(now), ErrorCode::RoundNotOver);
+        game_account.verify_word(&word, &salt, &answer_proof)?;
+        require!(
+            !matches!(ctx.accounts.guess_log.outcome(&word), GuessOutcome::Winner(_)),
+            ErrorCode::GameAlreadyDecided
+        );
         
         // Update game state
+        game_account.status = GameStatus::Completed;
+        game_account.completed_at = now;
+        
+        let pot = Pot::new(game_account, &ctx.accounts.mint, &ctx.accounts.vault, &ctx.accounts.token_program)?;
+        let creator = Payee::new(&ctx.accounts.creator, &ctx.accounts.creator_token_account);
+        let (winner_amount, creator_refund, opponent_refund, fee_amount) = pay_out(
+            game_account,
+            &pot,
+            &creator,
+            &Payee::new(&ctx.accounts.opponent, &ctx.accounts.opponent_token_account),
+            &Payee::new(&ctx.accounts.house_wallet, &ctx.accounts.house_token_account),
+            None,
+            false,
+        )?;
+        pot.close(game_account, &creator)?;
+        
+        // Record the outcome before the account is closed
+        emit!(GameSettled {
+            game: game_account.key(),
+            outcome: GameOutcome::BothLost,
+            winner: game_account.winner,
+            word,
+            winner_amount,
+            creator_refund,
+            opponent_refund,
+            fee_amount,
+            completed_at: game_account.completed_at,
+        });
+        
+        Ok(())
+    }
+
+    // Settle from the revealed word and the guess log instead of trusting the caller (settlement authority only)
+    // The first logged guess matching the word wins; if both players use all 6 guesses without it, both lose
+    // The game account and guess log are closed afterwards
+    pub fn settle_verified(
+        ctx: Context<SettleVerified>,
+        word: [u8; WORD_LENGTH],
+        salt: [u8; 32],
+        answer_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        
+        require!(game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
+        game_account.verify_word(&word, &salt, &answer_proof)?;
+        
+        let winner = match ctx.accounts.guess_log.outcome(&word) {
+            GuessOutcome::Winner(index) => Some(game_account.players[index]),
+            GuessOutcome::BothLost => None,
+            GuessOutcome::Undecided => return err!(ErrorCode::GameNotDecided),
+        };
+        
         game_account.status = GameStatus::Completed;
         game_account.completed_at = Clock::get()?.unix_timestamp;
         
-        let total_amount = escrow_account.total_amount;
-        let escrow_info = ctx.accounts.escrow_account.to_account_info();
-        let house_info = ctx.accounts.house_wallet.to_account_info();
+        let pot = Pot::new(game_account, &ctx.accounts.mint, &ctx.accounts.vault, &ctx.accounts.token_program)?;
+        let creator = Payee::new(&ctx.accounts.creator, &ctx.accounts.creator_token_account);
+        let (winner_amount, creator_refund, opponent_refund, fee_amount) = pay_out(
+            game_account,
+            &pot,
+            &creator,
+            &Payee::new(&ctx.accounts.opponent, &ctx.accounts.opponent_token_account),
+            &Payee::new(&ctx.accounts.house_wallet, &ctx.accounts.house_token_account),
+            winner,
+            false,
+        )?;
+        pot.close(game_account, &creator)?;
         
-        if both_lost {
-            // Both players lost - entire pot goes to house
-            game_account.winner = Pubkey::default(); // No winner
-            
-            // Get rent-exempt minimum
-            let rent = Rent::get()?;
-            let min_rent = rent.minimum_balance(escrow_account.to_account_info().data_len());
-            let available_amount = total_amount.saturating_sub(min_rent);
-            
-            **escrow_info.try_borrow_mut_lamports()? -= available_amount;
-            **house_info.try_borrow_mut_lamports()? += available_amount;
-            
-            msg!("Both players lost! House gets {} lamports", available_amount);
-        } else {
-            // There's a winner
-            require!(winner == game_account.players[0] || winner == game_account.players[1], ErrorCode::InvalidWinner);
-            game_account.winner = winner;
-            
-            // Calculate fee
-            let fee_bps = if is_forfeit { FORFEIT_FEE_BPS } else { WINNER_FEE_BPS };
-            let fee_amount = (total_amount * fee_bps) / 10000; // basis points to percentage
-            let winner_amount = total_amount - fee_amount;
-            
-            // Get winner account
-            let winner_account = if winner == game_account.players[0] {
-                &ctx.accounts.creator
-            } else {
-                &ctx.accounts.opponent
-            };
-            let winner_info = winner_account.to_account_info();
-            
-            // Get rent-exempt minimum for escrow account
-            let rent = Rent::get()?;
-            let min_rent = rent.minimum_balance(escrow_account.to_account_info().data_len());
-            
-            // Ensure we leave minimum rent in escrow
-            let available_amount = total_amount.saturating_sub(min_rent);
-            let actual_fee = (available_amount * fee_bps) / 10000;
-            let actual_winner_amount = available_amount - actual_fee;
-            
-            // Transfer fee to house
-            **escrow_info.try_borrow_mut_lamports()? -= actual_fee;
-            **house_info.try_borrow_mut_lamports()? += actual_fee;
-            
-            // Transfer winnings to winner
-            **escrow_info.try_borrow_mut_lamports()? -= actual_winner_amount;
-            **winner_info.try_borrow_mut_lamports()? += actual_winner_amount;
-            
-            msg!("Game settled! Winner: {} gets {} lamports, House fee: {} lamports", winner, winner_amount, fee_amount);
+        emit!(GameSettled {
+            game: game_account.key(),
+            outcome: if winner.is_some() { GameOutcome::Won } else { GameOutcome::BothLost },
+            winner: game_account.winner,
+            word,
+            winner_amount,
+            creator_refund,
+            opponent_refund,
+            fee_amount,
+            completed_at: game_account.completed_at,
+        });
+        
+        Ok(())
+    }
+
+    // Start a sudden-death round after both players failed to solve (settlement authority only)
+    // Only for SuddenDeath games, up to the game's max_tiebreak_rounds; the lost round's word is revealed
+    // for auditing and `commitment` is sha256(word || salt) for the new round's word
+    // The pot stays in the game and the guess log is cleared. The new answer comes from the same, still
+    // unrevealed seeds with the round mixed in (wordle_rules::seed::round_slot_hash)
+    // The seeds stay secret, so the lost word is proven to sit in the answer list at `answer_index`
+    // but that index can only be checked against the seeds once they are revealed (see TiebreakStarted)
+    pub fn start_tiebreak(
+        ctx: Context<StartTiebreak>,
+        word: [u8; WORD_LENGTH],
+        salt: [u8; 32],
+        answer_index: u32,
+        answer_proof: Vec<[u8; 32]>,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let guess_log = &mut ctx.accounts.guess_log;
+        
+        require!(game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
+        require!(game_account.both_lost_policy == BothLostPolicy::SuddenDeath, ErrorCode::TiebreakNotAllowed);
+        require!(game_account.round < game_account.max_tiebreak_rounds, ErrorCode::TiebreakNotAllowed);
+        // Once a seed is public the next answer would be too
+        require!(game_account.seeds == [None, None], ErrorCode::SeedsAlreadyRevealed);
+        
+        let previous_commitment = game_account.word_commitment.ok_or(ErrorCode::WordNotCommitted)?;
+        require!(hashv(&[&word, &salt]).to_bytes() == previous_commitment, ErrorCode::WordCommitmentMismatch);
+        require!(wordle_rules::is_valid_word(&word), ErrorCode::InvalidWord);
+        require!(
+            merkle::verify(&sha256, &game_account.answer_root, &merkle::answer_leaf_data(answer_index, &word), &answer_proof),
+            ErrorCode::WordNotInAnswerList
+        );
+        // Only a lost round: both players used all their guesses, or the round timed out without a solve
+        // (the same timeout settle_game accepts)
+        let now = Clock::get()?.unix_timestamp;
+        match guess_log.outcome(&word) {
+            GuessOutcome::BothLost => {}
+            GuessOutcome::Undecided => require!(game_account.round_over(now), ErrorCode::RoundNotOver),
+            GuessOutcome::Winner(_) => return err!(ErrorCode::GameAlreadyDecided),
         }
         
+        game_account.round += 1;
+        game_account.word_commitment = Some(commitment);
+        game_account.draw_offers = [false, false];
+        // The stale refund timer restarts with the new round
+        game_account.started_at = now;
+        guess_log.guesses.clear();
+        
+        emit!(TiebreakStarted {
+            game: game_account.key(),
+            round: game_account.round,
+            previous_word: word,
+            previous_answer_index: answer_index,
+            seed_slot_hash: game_account.seed_slot_hash,
+            word_commitment: commitment,
+            started_at: now,
+        });
+        
+        msg!("Tiebreak round {} started for game {}", game_account.round, game_account.key());
+        Ok(())
+    }
+
+    // Offer a draw, or accept the other player's offer (player only)
+    // Once both players have offered, anyone can settle the game as a draw
+    pub fn offer_draw(ctx: Context<OfferDraw>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let player = ctx.accounts.player.key();
+        
+        require!(game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
+        let index = game_account
+            .players
+            .iter()
+            .position(|p| *p == player)
+            .ok_or(ErrorCode::InvalidPlayerAccount)?;
+        require!(!game_account.draw_offers[index], ErrorCode::DrawAlreadyOffered);
+        
+        game_account.draw_offers[index] = true;
+        
+        emit!(DrawOffered {
+            game: game_account.key(),
+            player,
+            agreed: game_account.draw_offers == [true, true],
+        });
+        
+        msg!("Draw offered by {} in game {}", player, game_account.key());
+        Ok(())
+    }
+
+    // Settle a game as a draw: each player gets their deposit back minus the draw fee, which goes to the house
+    // Agreed by both players through offer_draw, or decided by the settlement authority, which must reveal
+    // the word so the program can check the guess log holds no winning solve
+    // The game account and guess log are closed afterwards
+    pub fn settle_draw(ctx: Context<SettleDraw>, reveal: Option<RevealedWord>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let authority = ctx.accounts.authority.key();
+        
+        require!(game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
+        let word = if game_account.draw_offers == [true, true] {
+            None
+        } else {
+            require_keys_eq!(authority, ctx.accounts.config.settlement_authority, ErrorCode::DrawNotAgreed);
+            let reveal = reveal.ok_or(ErrorCode::WordNotRevealed)?;
+            game_account.verify_word(&reveal.word, &reveal.salt, &reveal.answer_proof)?;
+            require!(
+                !matches!(ctx.accounts.guess_log.outcome(&reveal.word), GuessOutcome::Winner(_)),
+                ErrorCode::GameAlreadyDecided
+            );
+            Some(reveal.word)
+        };
+        
+        game_account.winner = Pubkey::default();
+        game_account.status = GameStatus::Completed;
+        game_account.completed_at = Clock::get()?.unix_timestamp;
+        
+        let pot = Pot::new(game_account, &ctx.accounts.mint, &ctx.accounts.vault, &ctx.accounts.token_program)?;
+        let creator = Payee::new(&ctx.accounts.creator, &ctx.accounts.creator_token_account);
+        let (creator_refund, opponent_refund, fee_amount) = refund_minus_fee(
+            game_account,
+            &pot,
+            &creator,
+            &Payee::new(&ctx.accounts.opponent, &ctx.accounts.opponent_token_account),
+            &Payee::new(&ctx.accounts.house_wallet, &ctx.accounts.house_token_account),
+        )?;
+        pot.close(game_account, &creator)?;
+        
+        emit!(GameDrawn {
+            game: game_account.key(),
+            settled_by: authority,
+            word,
+            creator_refund,
+            opponent_refund,
+            fee_amount,
+            completed_at: game_account.completed_at,
+        });
+        
+        msg!("Game drawn: creator gets {}, opponent gets {}, House fee: {}", creator_refund, opponent_refund, fee_amount);
         Ok(())
     }
 
     // Cancel game - refund creator (only for waiting games)
+    // The account is closed to the creator (wager + rent), so nothing later in the
+    // same transaction - e.g. join_game - can use it
     pub fn cancel_game(ctx: Context<CancelGame>) -> Result<()> {
         let game_account = &mut ctx.accounts.game_account;
-        let escrow_account = &mut ctx.accounts.escrow_account;
         
         require!(game_account.status == GameStatus::Waiting, ErrorCode::GameNotWaiting);
-        require!(ctx.accounts.creator.key() == game_account.creator, ErrorCode::Unauthorized);
         
-        // Get refund amount and account rents before closing
-        let refund_amount = escrow_account.creator_deposited;
-        let escrow_info = ctx.accounts.escrow_account.to_account_info();
-        let game_info = ctx.accounts.game_account.to_account_info();
-        let creator_info = ctx.accounts.creator.to_account_info();
+        game_account.status = GameStatus::Cancelled;
+        game_account.completed_at = Clock::get()?.unix_timestamp;
         
-        // Calculate total refund including all lamports in both accounts
-        // This includes the wager + rent reserves from both game and escrow accounts
-        let escrow_lamports = escrow_info.lamports();
-        let game_lamports = game_info.lamports();
-        let total_refund = escrow_lamports + game_lamports;
+        // Refund all lamports in the game account (wager + rent reserve); token games refund the vault too
+        let pot = Pot::new(game_account, &ctx.accounts.mint, &ctx.accounts.vault, &ctx.accounts.token_program)?;
+        let creator = Payee::new(&ctx.accounts.creator, &ctx.accounts.creator_token_account);
+        pot.pay(game_account, &creator, game_account.creator_deposited)?;
+        pot.close(game_account, &creator)?;
         
-        // Transfer all lamports from both accounts to creator
-        **escrow_info.try_borrow_mut_lamports()? = 0;
-        **game_info.try_borrow_mut_lamports()? = 0;
-        **creator_info.try_borrow_mut_lamports()? += total_refund;
+        emit!(GameCancelled {
+            game: game_account.key(),
+            creator: game_account.creator,
+            refund_amount: game_account.creator_deposited,
+            cancelled_at: game_account.completed_at,
+        });
         
-        msg!("Game cancelled, total refunded (including rent): {} lamports", total_refund);
+        let unit = if game_account.mint.is_some() { "tokens" } else { "lamports" };
+        msg!("Game cancelled, creator refunded {} {} plus rent", game_account.creator_deposited, unit);
         Ok(())
     }
     
-    // Forfeit game - player gives up during active game (5% fee)
+    // Forfeit game - player gives up during active game (forfeit fee)
+    // Signed by the forfeiting player, or by the settlement authority when the server detects abandonment
     pub fn forfeit_game(ctx: Context<ForfeitGame>, forfeiter: Pubkey) -> Result<()> {
         let game_account = &mut ctx.accounts.game_account;
-        let escrow_account = &mut ctx.accounts.escrow_account;
+        let authority = ctx.accounts.authority.key();
         
         require!(game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
         require!(forfeiter == game_account.players[0] || forfeiter == game_account.players[1], ErrorCode::InvalidWinner);
         
+        let forfeit_kind = if authority == forfeiter {
+            ForfeitKind::Voluntary
+        } else if authority == ctx.accounts.config.settlement_authority {
+            ForfeitKind::Abandoned
+        } else {
+            return err!(ErrorCode::UnauthorizedForfeit);
+        };
+        
         // Determine winner (the non-forfeiter)
         let winner = if forfeiter == game_account.players[0] {
             game_account.players[1]
@@ -203,98 +663,777 @@ pub mod wordle_escrow {
             game_account.players[0]
         };
         
-        game_account.winner = winner;
         game_account.status = GameStatus::Completed;
         game_account.completed_at = Clock::get()?.unix_timestamp;
         
-        let total_amount = escrow_account.total_amount;
-        let escrow_info = ctx.accounts.escrow_account.to_account_info();
-        let house_info = ctx.accounts.house_wallet.to_account_info();
+        let pot = Pot::new(game_account, &ctx.accounts.mint, &ctx.accounts.vault, &ctx.accounts.token_program)?;
+        let creator = Payee::new(&ctx.accounts.creator, &ctx.accounts.creator_token_account);
+        let (winner_amount, _, _, fee_amount) = pay_out(
+            game_account,
+            &pot,
+            &creator,
+            &Payee::new(&ctx.accounts.opponent, &ctx.accounts.opponent_token_account),
+            &Payee::new(&ctx.accounts.house_wallet, &ctx.accounts.house_token_account),
+            Some(winner),
+            true,
+        )?;
+        pot.close(game_account, &creator)?;
+        
+        match forfeit_kind {
+            ForfeitKind::Abandoned => msg!("Game abandoned by {} (declared by settlement authority)! Winner: {} gets {}, House fee: {}", forfeiter, winner, winner_amount, fee_amount),
+            ForfeitKind::Voluntary => msg!("Game forfeited by {}! Winner: {} gets {}, House fee: {}", forfeiter, winner, winner_amount, fee_amount),
+        }
+        
+        // Record the outcome before the account is closed
+        emit!(GameForfeited {
+            game: game_account.key(),
+            forfeiter,
+            winner,
+            forfeit_kind,
+            winner_amount,
+            fee_amount,
+            completed_at: game_account.completed_at,
+        });
+        
+        Ok(())
+    }
+
+    // Expire a waiting game whose join deadline has passed (anyone can crank this)
+    // The creator gets their wager and the rent back, minus the crank reward paid out of the rent
+    pub fn expire_game(ctx: Context<ExpireGame>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(game_account.status == GameStatus::Waiting, ErrorCode::GameNotWaiting);
+        let deadline = game_account.join_deadline.ok_or(ErrorCode::NoJoinDeadline)?;
+        require!(now > deadline, ErrorCode::JoinDeadlineNotReached);
+        
+        game_account.status = GameStatus::Cancelled;
+        game_account.completed_at = now;
+        
+        let pot = Pot::new(game_account, &ctx.accounts.mint, &ctx.accounts.vault, &ctx.accounts.token_program)?;
+        let creator = Payee::new(&ctx.accounts.creator, &ctx.accounts.creator_token_account);
+        pot.pay(game_account, &creator, game_account.creator_deposited)?;
+        pot.close(game_account, &creator)?;
+        
+        // The reward can only come out of the rent, never the creator's wager (already refunded above)
+        let game_info = game_account.to_account_info();
+        let crank_reward = game_account.crank_reward.min(game_info.lamports());
+        transfer_lamports(&game_info, &ctx.accounts.cranker.to_account_info(), crank_reward)?;
+        
+        emit!(GameExpired {
+            game: game_info.key(),
+            creator: game_account.creator,
+            cranker: ctx.accounts.cranker.key(),
+            refund_amount: game_account.creator_deposited,
+            crank_reward,
+            expired_at: now,
+        });
+        
+        let unit = if game_account.mint.is_some() { "tokens" } else { "lamports" };
+        msg!("Game expired, creator refunded {} {}, crank reward: {} lamports", game_account.creator_deposited, unit, crank_reward);
+        Ok(())
+    }
+
+    // Refund both deposits (no fee) when a started game was never settled within the max game duration
+    // Either player can call this, so funds never depend on the server staying up
+    pub fn claim_stale_refund(ctx: Context<ClaimStaleRefund>) -> Result<()> {
+        let game_account = &mut ctx.accounts.game_account;
+        let player = ctx.accounts.player.key();
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(game_account.status == GameStatus::Playing, ErrorCode::GameNotPlaying);
+        require!(player == game_account.players[0] || player == game_account.players[1], ErrorCode::Unauthorized);
+        require!(now >= game_account.started_at.saturating_add(game_account.max_game_duration), ErrorCode::GameNotStale);
+        
+        game_account.status = GameStatus::Cancelled;
+        game_account.completed_at = now;
+        
+        let creator_refund = game_account.creator_deposited;
+        let opponent_refund = game_account.opponent_deposited;
         
-        // Get rent-exempt minimum
-        let rent = Rent::get()?;
-        let min_rent = rent.minimum_balance(escrow_account.to_account_info().data_len());
-        let available_amount = total_amount.saturating_sub(min_rent);
+        let pot = Pot::new(game_account, &ctx.accounts.mint, &ctx.accounts.vault, &ctx.accounts.token_program)?;
+        let creator = Payee::new(&ctx.accounts.creator, &ctx.accounts.creator_token_account);
+        pot.pay(game_account, &creator, creator_refund)?;
+        pot.pay(game_account, &Payee::new(&ctx.accounts.opponent, &ctx.accounts.opponent_token_account), opponent_refund)?;
+        pot.close(game_account, &creator)?;
         
-        let fee_amount = (available_amount * FORFEIT_FEE_BPS) / 10000; // 5% forfeit fee
-        let winner_amount = available_amount - fee_amount;
+        emit!(GameRefunded {
+            game: game_account.key(),
+            claimed_by: player,
+            creator_refund,
+            opponent_refund,
+            refunded_at: now,
+        });
         
-        // Get winner account
-        let winner_account = if winner == game_account.players[0] {
-            &ctx.accounts.creator
+        msg!("Stale game refunded: creator gets {}, opponent gets {}", creator_refund, opponent_refund);
+        Ok(())
+    }
+
+    // Close a completed game left open by an older program version and return its rent to the creator
+    pub fn close_game(ctx: Context<CloseGame>) -> Result<()> {
+        require!(ctx.accounts.game_account.status == GameStatus::Completed, ErrorCode::GameNotCompleted);
+        
+        msg!("Completed game closed: {}", ctx.accounts.game_account.key());
+        Ok(())
+    }
+
+    // Refund a game created before games were PDAs: a keypair game account plus a separate escrow account
+    // These games can no longer be played or settled, so each player gets their deposit back and the
+    // creator also gets both accounts' rent. Permissionless: funds only go to the game's recorded players
+    pub fn recover_legacy_game(ctx: Context<RecoverLegacyGame>) -> Result<()> {
+        let game_info = ctx.accounts.game_account.to_account_info();
+        let escrow_info = ctx.accounts.escrow_account.to_account_info();
+        let creator_info = ctx.accounts.creator.to_account_info();
+        
+        let legacy = LegacyGameAccount::try_from_account_info(&game_info)?;
+        require_keys_eq!(creator_info.key(), legacy.creator, ErrorCode::InvalidPlayerAccount);
+        
+        // Only a started game still holds the opponent's deposit; settled ones keep just the escrow rent
+        let opponent_refund = if legacy.status == GameStatus::Playing {
+            ctx.accounts.escrow_account.opponent_deposited
         } else {
-            &ctx.accounts.opponent
+            0
         };
-        let winner_info = winner_account.to_account_info();
-        
-        // Transfer fee to house
-        **escrow_info.try_borrow_mut_lamports()? -= fee_amount;
-        **house_info.try_borrow_mut_lamports()? += fee_amount;
+        if opponent_refund > 0 {
+            let opponent = ctx.accounts.opponent.as_ref().ok_or(ErrorCode::InvalidPlayerAccount)?;
+            require_keys_eq!(opponent.key(), legacy.players[1], ErrorCode::InvalidPlayerAccount);
+            transfer_lamports(&escrow_info, &opponent.to_account_info(), opponent_refund)?;
+        }
         
-        // Transfer winnings to winner
-        **escrow_info.try_borrow_mut_lamports()? -= winner_amount;
-        **winner_info.try_borrow_mut_lamports()? += winner_amount;
+        // The escrow is closed to the creator by Anchor; the game account is closed here
+        let creator_refund = escrow_info.lamports() + game_info.lamports();
+        transfer_lamports(&game_info, &creator_info, game_info.lamports())?;
+        game_info.assign(&system_program::ID);
+        game_info.realloc(0, false)?;
         
-        msg!("Game forfeited! Winner: {} gets {} lamports, House fee: {} lamports", winner, winner_amount, fee_amount);
+        msg!("Legacy game recovered: {}, creator gets {}, opponent gets {}", game_info.key(), creator_refund, opponent_refund);
         Ok(())
     }
 }
 
+// Pay the pot out of the game account: the fee to the house and the rest to the winner,
+// or as the game's both-lost policy says when there is no winner
+// Returns the winner's amount, the creator's and opponent's refunds and the house fee
+fn pay_out<'info>(
+    game_account: &mut Account<'info, GameAccount>,
+    pot: &Pot<'_, 'info>,
+    creator: &Payee<'_, 'info>,
+    opponent: &Payee<'_, 'info>,
+    house: &Payee<'_, 'info>,
+    winner: Option<Pubkey>,
+    is_forfeit: bool,
+) -> Result<(u64, u64, u64, u64)> {
+    let total_amount = game_account.total_amount;
+    
+    let Some(winner) = winner else {
+        game_account.winner = Pubkey::default(); // No winner
+        
+        // Sudden death falls back to the game's fallback policy once the tiebreak rounds are used up
+        let policy = match game_account.both_lost_policy {
+            BothLostPolicy::SuddenDeath if game_account.round < game_account.max_tiebreak_rounds => {
+                return err!(ErrorCode::TiebreakRequired);
+            }
+            BothLostPolicy::SuddenDeath => game_account.tiebreak_fallback,
+            policy => policy,
+        };
+        match policy {
+            BothLostPolicy::HouseTakesAll => {
+                pot.pay(game_account, house, total_amount)?;
+                msg!("Both players lost! House gets {}", total_amount);
+                return Ok((0, 0, 0, total_amount));
+            }
+            // The fallback is never SuddenDeath (see ConfigParams::validate)
+            BothLostPolicy::RefundMinusFee | BothLostPolicy::SuddenDeath => {
+                let (creator_refund, opponent_refund, fee_amount) = refund_minus_fee(game_account, pot, creator, opponent, house)?;
+                msg!("Both players lost! Refunds: {} and {}, House fee: {}", creator_refund, opponent_refund, fee_amount);
+                return Ok((0, creator_refund, opponent_refund, fee_amount));
+            }
+        }
+    };
+    
+    require!(winner == game_account.players[0] || winner == game_account.players[1], ErrorCode::InvalidWinner);
+    game_account.winner = winner;
+    
+    // Calculate fee
+    let fee_bps = if is_forfeit { game_account.forfeit_fee_bps } else { game_account.winner_fee_bps };
+    let fee_amount = fee(total_amount, fee_bps);
+    let winner_amount = total_amount - fee_amount;
+    
+    // Get winner account
+    let winner_payee = if winner == game_account.players[0] { creator } else { opponent };
+    
+    pot.pay(game_account, house, fee_amount)?;
+    pot.pay(game_account, winner_payee, winner_amount)?;
+    
+    msg!("Game settled! Winner: {} gets {}, House fee: {}", winner, winner_amount, fee_amount);
+    Ok((winner_amount, 0, 0, fee_amount))
+}
+
+// `bps` of `amount`, rounded down; done in u128 since amount * bps can overflow u64 for large token wagers
+fn fee(amount: u64, bps: u64) -> u64 {
+    (amount as u128 * bps as u128 / MAX_FEE_BPS as u128) as u64
+}
+
+// Give each player their deposit back minus the draw fee, which goes to the house
+// The fee is taken from each deposit, so each player loses the same share of what they put in
+// Returns the creator's refund, the opponent's refund and the total fee
+fn refund_minus_fee<'info>(
+    game_account: &Account<'info, GameAccount>,
+    pot: &Pot<'_, 'info>,
+    creator: &Payee<'_, 'info>,
+    opponent: &Payee<'_, 'info>,
+    house: &Payee<'_, 'info>,
+) -> Result<(u64, u64, u64)> {
+    let creator_fee = fee(game_account.creator_deposited, game_account.draw_fee_bps);
+    let opponent_fee = fee(game_account.opponent_deposited, game_account.draw_fee_bps);
+    let creator_refund = game_account.creator_deposited - creator_fee;
+    let opponent_refund = game_account.opponent_deposited - opponent_fee;
+    let fee_amount = creator_fee + opponent_fee;
+    
+    pot.pay(game_account, house, fee_amount)?;
+    pot.pay(game_account, creator, creator_refund)?;
+    pot.pay(game_account, opponent, opponent_refund)?;
+    Ok((creator_refund, opponent_refund, fee_amount))
+}
+
+// Where a game's pot is held: lamports on the game account itself, or the game PDA's token vault
+// Token games work with both the Token and Token-2022 programs
+enum Pot<'a, 'info> {
+    Lamports,
+    Tokens {
+        mint: &'a InterfaceAccount<'info, Mint>,
+        vault: &'a InterfaceAccount<'info, TokenAccount>,
+        token_program: &'a Interface<'info, TokenInterface>,
+    },
+}
+
+// Who a payout goes to: their wallet for SOL games, their token account for the game's mint in token games
+struct Payee<'a, 'info> {
+    wallet: &'a AccountInfo<'info>,
+    token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+}
+
+impl<'a, 'info> Payee<'a, 'info> {
+    fn new(wallet: &'a AccountInfo<'info>, token_account: &'a Option<InterfaceAccount<'info, TokenAccount>>) -> Self {
+        Self { wallet, token_account: token_account.as_ref() }
+    }
+}
+
+impl<'a, 'info> Pot<'a, 'info> {
+    // Token games must pass their mint, vault and the mint's token program
+    fn new(
+        game_account: &GameAccount,
+        mint: &'a Option<InterfaceAccount<'info, Mint>>,
+        vault: &'a Option<InterfaceAccount<'info, TokenAccount>>,
+        token_program: &'a Option<Interface<'info, TokenInterface>>,
+    ) -> Result<Self> {
+        match (game_account.mint, mint, vault, token_program) {
+            (None, _, _, _) => Ok(Pot::Lamports),
+            (Some(_), Some(mint), Some(vault), Some(token_program)) => Ok(Pot::Tokens { mint, vault, token_program }),
+            _ => err!(ErrorCode::MissingTokenAccounts),
+        }
+    }
+
+    // Move a player's wager into the pot and return the amount that actually arrived
+    // Token-2022 transfer fees are withheld from what the vault receives, so the game records that instead
+    fn deposit(
+        &self,
+        game_account: &Account<'info, GameAccount>,
+        player: &Signer<'info>,
+        player_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+        system_program: &Program<'info, System>,
+        amount: u64,
+    ) -> Result<u64> {
+        let Pot::Tokens { mint, vault, token_program } = self else {
+            let transfer_instruction = system_program::Transfer {
+                from: player.to_account_info(),
+                to: game_account.to_account_info(),
+            };
+            let cpi_context = CpiContext::new(system_program.to_account_info(), transfer_instruction);
+            system_program::transfer(cpi_context, amount)?;
+            return Ok(amount);
+        };
+        
+        let from = player_token_account.ok_or(ErrorCode::MissingTokenAccounts)?;
+        let before = token_balance(&vault.to_account_info())?;
+        let transfer_instruction = token_interface::TransferChecked {
+            from: from.to_account_info(),
+            mint: mint.to_account_info(),
+            to: vault.to_account_info(),
+            authority: player.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(token_program.to_account_info(), transfer_instruction);
+        token_interface::transfer_checked(cpi_context, amount, mint.decimals)?;
+        
+        received_amount(before, token_balance(&vault.to_account_info())?)
+    }
+
+    fn pay(&self, game_account: &Account<'info, GameAccount>, payee: &Payee<'_, 'info>, amount: u64) -> Result<()> {
+        match self {
+            Pot::Lamports => transfer_lamports(&game_account.to_account_info(), payee.wallet, amount),
+            Pot::Tokens { mint, vault, token_program } => {
+                let to = payee.token_account.ok_or(ErrorCode::MissingTokenAccounts)?;
+                let transfer_instruction = token_interface::TransferChecked {
+                    from: vault.to_account_info(),
+                    mint: mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: game_account.to_account_info(),
+                };
+                let creator = game_account.creator;
+                let nonce = game_account.nonce.to_le_bytes();
+                let signer_seeds: &[&[&[u8]]] = &[&[GAME_SEED, creator.as_ref(), &nonce, &[game_account.bump]]];
+                let cpi_context = CpiContext::new_with_signer(token_program.to_account_info(), transfer_instruction, signer_seeds);
+                token_interface::transfer_checked(cpi_context, amount, mint.decimals)
+            }
+        }
+    }
+
+    // Close the vault once the pot is paid out, returning its rent to the creator
+    // Tokens sent to the vault outside the game would block the close, so they go to the creator first,
+    // and Token-2022 transfer fees withheld in the vault are harvested to the mint (anyone may do this)
+    // SOL games need nothing here: the game account itself is closed to the creator
+    fn close(&self, game_account: &Account<'info, GameAccount>, creator: &Payee<'_, 'info>) -> Result<()> {
+        let Pot::Tokens { mint, vault, token_program } = self else {
+            return Ok(());
+        };
+        let stray = vault.amount.saturating_sub(game_account.total_amount);
+        if stray > 0 {
+            self.pay(game_account, creator, stray)?;
+        }
+        if withheld_fees(&vault.to_account_info())? > 0 {
+            let harvest = harvest_withheld_tokens_to_mint(&token_program.key(), &mint.key(), &[&vault.key()])?;
+            invoke(&harvest, &[mint.to_account_info(), vault.to_account_info()])?;
+        }
+        let close_instruction = token_interface::CloseAccount {
+            account: vault.to_account_info(),
+            destination: creator.wallet.clone(),
+            authority: game_account.to_account_info(),
+        };
+        let creator_key = game_account.creator;
+        let nonce = game_account.nonce.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[GAME_SEED, creator_key.as_ref(), &nonce, &[game_account.bump]]];
+        token_interface::close_account(CpiContext::new_with_signer(token_program.to_account_info(), close_instruction, signer_seeds))
+    }
+}
+
+// Current balance of a token account, re-read so it reflects CPIs made earlier in the instruction
+fn token_balance(info: &AccountInfo) -> Result<u64> {
+    Ok(TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?.amount)
+}
+
+// What a deposit left in the vault, from its balance before and after the transfer
+// A fee that swallows the whole wager (or a balance that went down) is rejected
+fn received_amount(before: u64, after: u64) -> Result<u64> {
+    let received = after.saturating_sub(before);
+    require!(received > 0, ErrorCode::InvalidWager);
+    Ok(received)
+}
+
+// Transfer fees withheld in a Token-2022 account; always zero for Token program accounts
+fn withheld_fees(info: &AccountInfo) -> Result<u64> {
+    if *info.owner != token_2022::ID {
+        return Ok(0);
+    }
+    let data = info.try_borrow_data()?;
+    let account = StateWithExtensions::<token_2022::spl_token_2022::state::Account>::unpack(&data)?;
+    Ok(account.get_extension::<TransferFeeAmount>().map_or(0, |fee| u64::from(fee.withheld_amount)))
+}
+
+fn sha256(parts: &[&[u8]]) -> [u8; 32] {
+    hashv(parts).to_bytes()
+}
+
+// Most recent (slot, hash) entry of the SlotHashes sysvar, read directly since it is too large to deserialize
+// Layout: u64 entry count, then (u64 slot, [u8; 32] hash) entries, newest first
+fn latest_slot_hash(slot_hashes: &AccountInfo) -> Result<(u64, [u8; 32])> {
+    let data = slot_hashes.try_borrow_data()?;
+    require!(data.len() >= 48, ErrorCode::InvalidSlotHashes);
+    require!(u64::from_le_bytes(data[..8].try_into().unwrap()) > 0, ErrorCode::InvalidSlotHashes);
+    let slot = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    let hash = data[16..48].try_into().unwrap();
+    Ok((slot, hash))
+}
+
+fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    **from.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
 #[derive(Accounts)]
-pub struct CreateGame<'info> {
+pub struct InitializeConfig<'info> {
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub admin: Signer<'info>,
     
     #[account(
         init,
-        payer = creator,
-        space = 8 + GameAccount::INIT_SPACE
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
     )]
-    pub game_account: Account<'info, GameAccount>,
+    pub config: Account<'info, Config>,
     
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + EscrowAccount::INIT_SPACE
-    )]
-    pub escrow_account: Account<'info, EscrowAccount>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::WordleEscrow>,
+    
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct JoinGame<'info> {
-    #[account(mut)]
-    pub opponent: Signer<'info>,
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
     
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintConfig<'info> {
     #[account(mut)]
-    pub game_account: Account<'info, GameAccount>,
+    pub admin: Signer<'info>,
     
-    #[account(mut)]
-    pub escrow_account: Account<'info, EscrowAccount>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump, has_one = admin @ ErrorCode::Unauthorized)]
+    pub config: Account<'info, Config>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MintConfig::INIT_SPACE,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct SettleGame<'info> {
+#[instruction(nonce: u64)]
+pub struct CreateGame<'info> {
     #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + GameAccount::INIT_SPACE,
+        seeds = [GAME_SEED, creator.key().as_ref(), &nonce.to_le_bytes()],
+        bump
+    )]
     pub game_account: Account<'info, GameAccount>,
     
+    /// Token games only: the wager mint, which must have a mint config
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// Token games only: the mint's config, checked against the mint in the handler
+    pub mint_config: Option<Account<'info, MintConfig>>,
+    
+    /// Token games only: the creator's token account the wager is paid from
     #[account(mut)]
-    pub escrow_account: Account<'info, EscrowAccount>,
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the game PDA's associated token account, holding both wagers
+    #[account(
+        init,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::authority = game_account,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub system_program: Program<'info, System>,
     
-    /// CHECK: This is the creator account
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+}
+
+#[derive(Accounts)]
+pub struct JoinGame<'info> {
     #[account(mut)]
-    pub creator: AccountInfo<'info>,
+    pub opponent: Signer<'info>,
     
-    /// CHECK: This is the opponent account
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    #[account(
+        init,
+        payer = opponent,
+        space = 8 + GuessLog::INIT_SPACE,
+        seeds = [GUESS_LOG_SEED, game_account.key().as_ref()],
+        bump
+    )]
+    pub guess_log: Account<'info, GuessLog>,
+    
+    /// CHECK: SlotHashes sysvar, read by hand in latest_slot_hash
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    
+    /// Token games only: the opponent's token account the wager is paid from
     #[account(mut)]
+    pub opponent_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the game's mint
+    #[account(constraint = Some(mint.key()) == game_account.mint @ ErrorCode::InvalidTokenAccount)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// Token games only: the game PDA's associated token account
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_account,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub system_program: Program<'info, System>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct RevealSeed<'info> {
+    pub revealer: Signer<'info>,
+    
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump
+    )]
+    pub game_account: Account<'info, GameAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuessDelegate<'info> {
+    pub player: Signer<'info>,
+    
+    #[account(mut, seeds = [GUESS_LOG_SEED, guess_log.game.as_ref()], bump = guess_log.bump)]
+    pub guess_log: Account<'info, GuessLog>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitGuess<'info> {
+    pub signer: Signer<'info>,
+    
+    #[account(
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    #[account(
+        mut,
+        seeds = [GUESS_LOG_SEED, game_account.key().as_ref()],
+        bump = guess_log.bump
+    )]
+    pub guess_log: Account<'info, GuessLog>,
+}
+
+#[derive(Accounts)]
+pub struct CloseGuessLog<'info> {
+    #[account(
+        mut,
+        seeds = [GUESS_LOG_SEED, guess_log.game.as_ref()],
+        bump = guess_log.bump,
+        close = opponent
+    )]
+    pub guess_log: Account<'info, GuessLog>,
+    
+    /// CHECK: Only checked to be closed; the game PDA is emptied on settle, cancel, forfeit and refund
+    #[account(address = guess_log.game)]
+    pub game_account: UncheckedAccount<'info>,
+    
+    /// CHECK: Rent goes back to the opponent who paid it
+    #[account(mut, address = guess_log.players[1] @ ErrorCode::InvalidPlayerAccount)]
     pub opponent: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CommitWord<'info> {
+    #[account(address = config.settlement_authority @ ErrorCode::InvalidSettlementAuthority)]
+    pub settlement_authority: Signer<'info>,
     
-    /// CHECK: This is the house wallet for fees
-    #[account(mut)]
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    #[account(seeds = [GUESS_LOG_SEED, game_account.key().as_ref()], bump = guess_log.bump)]
+    pub guess_log: Account<'info, GuessLog>,
+}
+
+#[derive(Accounts)]
+pub struct SettleGame<'info> {
+    #[account(address = config.settlement_authority @ ErrorCode::InvalidSettlementAuthority)]
+    pub settlement_authority: Signer<'info>,
+    
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump,
+        close = creator
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    #[account(
+        mut,
+        seeds = [GUESS_LOG_SEED, game_account.key().as_ref()],
+        bump = guess_log.bump,
+        close = opponent
+    )]
+    pub guess_log: Account<'info, GuessLog>,
+    
+    /// CHECK: Must be players[0] recorded on the game
+    #[account(mut, address = game_account.players[0] @ ErrorCode::InvalidPlayerAccount)]
+    pub creator: AccountInfo<'info>,
+    
+    /// CHECK: Must be players[1] recorded on the game
+    #[account(mut, address = game_account.players[1] @ ErrorCode::InvalidPlayerAccount)]
+    pub opponent: AccountInfo<'info>,
+    
+    /// CHECK: Must be the house wallet recorded in config
+    #[account(mut, address = config.house_wallet @ ErrorCode::InvalidHouseWallet)]
     pub house_wallet: AccountInfo<'info>,
+    
+    /// Token games only: the game's mint (writable so withheld Token-2022 fees can be harvested to it)
+    #[account(mut, constraint = Some(mint.key()) == game_account.mint @ ErrorCode::InvalidTokenAccount)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// Token games only: the game PDA's associated token account
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_account,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the creator's token account for the game's mint
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == game_account.players[0] && Some(creator_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the opponent's token account for the game's mint
+    #[account(
+        mut,
+        constraint = opponent_token_account.owner == game_account.players[1] && Some(opponent_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub opponent_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the house wallet's token account for the game's mint
+    #[account(
+        mut,
+        constraint = house_token_account.owner == config.house_wallet && Some(house_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub house_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct SettleVerified<'info> {
+    #[account(address = config.settlement_authority @ ErrorCode::InvalidSettlementAuthority)]
+    pub settlement_authority: Signer<'info>,
+    
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump,
+        close = creator
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    #[account(
+        mut,
+        seeds = [GUESS_LOG_SEED, game_account.key().as_ref()],
+        bump = guess_log.bump,
+        close = opponent
+    )]
+    pub guess_log: Account<'info, GuessLog>,
+    
+    /// CHECK: Must be players[0] recorded on the game
+    #[account(mut, address = game_account.players[0] @ ErrorCode::InvalidPlayerAccount)]
+    pub creator: AccountInfo<'info>,
+    
+    /// CHECK: Must be players[1] recorded on the game
+    #[account(mut, address = game_account.players[1] @ ErrorCode::InvalidPlayerAccount)]
+    pub opponent: AccountInfo<'info>,
+    
+    /// CHECK: Must be the house wallet recorded in config
+    #[account(mut, address = config.house_wallet @ ErrorCode::InvalidHouseWallet)]
+    pub house_wallet: AccountInfo<'info>,
+    
+    /// Token games only: the game's mint (writable so withheld Token-2022 fees can be harvested to it)
+    #[account(mut, constraint = Some(mint.key()) == game_account.mint @ ErrorCode::InvalidTokenAccount)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// Token games only: the game PDA's associated token account
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_account,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the creator's token account for the game's mint
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == game_account.players[0] && Some(creator_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the opponent's token account for the game's mint
+    #[account(
+        mut,
+        constraint = opponent_token_account.owner == game_account.players[1] && Some(opponent_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub opponent_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the house wallet's token account for the game's mint
+    #[account(
+        mut,
+        constraint = house_token_account.owner == config.house_wallet && Some(house_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub house_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
 #[derive(Accounts)]
@@ -302,47 +1441,588 @@ pub struct CancelGame<'info> {
     #[account(mut)]
     pub creator: Signer<'info>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump,
+        has_one = creator @ ErrorCode::Unauthorized,
+        close = creator
+    )]
     pub game_account: Account<'info, GameAccount>,
     
-    #[account(mut)]
-    pub escrow_account: Account<'info, EscrowAccount>,
+    /// Token games only: the game's mint (writable so withheld Token-2022 fees can be harvested to it)
+    #[account(mut, constraint = Some(mint.key()) == game_account.mint @ ErrorCode::InvalidTokenAccount)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// Token games only: the game PDA's associated token account
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_account,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the creator's token account for the game's mint
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == game_account.players[0] && Some(creator_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
 #[derive(Accounts)]
 pub struct ForfeitGame<'info> {
-    #[account(mut)]
+    /// The forfeiting player, or the settlement authority forfeiting on their behalf
+    pub authority: Signer<'info>,
+    
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump,
+        close = creator
+    )]
     pub game_account: Account<'info, GameAccount>,
     
-    #[account(mut)]
-    pub escrow_account: Account<'info, EscrowAccount>,
+    #[account(
+        mut,
+        seeds = [GUESS_LOG_SEED, game_account.key().as_ref()],
+        bump = guess_log.bump,
+        close = opponent
+    )]
+    pub guess_log: Account<'info, GuessLog>,
     
-    /// CHECK: This is the creator account
-    #[account(mut)]
+    /// CHECK: Must be players[0] recorded on the game
+    #[account(mut, address = game_account.players[0] @ ErrorCode::InvalidPlayerAccount)]
     pub creator: AccountInfo<'info>,
     
-    /// CHECK: This is the opponent account
+    /// CHECK: Must be players[1] recorded on the game
+    #[account(mut, address = game_account.players[1] @ ErrorCode::InvalidPlayerAccount)]
+    pub opponent: AccountInfo<'info>,
+    
+    /// CHECK: Must be the house wallet recorded in config
+    #[account(mut, address = config.house_wallet @ ErrorCode::InvalidHouseWallet)]
+    pub house_wallet: AccountInfo<'info>,
+    
+    /// Token games only: the game's mint (writable so withheld Token-2022 fees can be harvested to it)
+    #[account(mut, constraint = Some(mint.key()) == game_account.mint @ ErrorCode::InvalidTokenAccount)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// Token games only: the game PDA's associated token account
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_account,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the creator's token account for the game's mint
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == game_account.players[0] && Some(creator_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the opponent's token account for the game's mint
+    #[account(
+        mut,
+        constraint = opponent_token_account.owner == game_account.players[1] && Some(opponent_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub opponent_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the house wallet's token account for the game's mint
+    #[account(
+        mut,
+        constraint = house_token_account.owner == config.house_wallet && Some(house_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub house_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct StartTiebreak<'info> {
+    #[account(address = config.settlement_authority @ ErrorCode::InvalidSettlementAuthority)]
+    pub settlement_authority: Signer<'info>,
+    
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    #[account(
+        mut,
+        seeds = [GUESS_LOG_SEED, game_account.key().as_ref()],
+        bump = guess_log.bump
+    )]
+    pub guess_log: Account<'info, GuessLog>,
+}
+
+#[derive(Accounts)]
+pub struct OfferDraw<'info> {
+    pub player: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump
+    )]
+    pub game_account: Account<'info, GameAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SettleDraw<'info> {
+    /// The settlement authority, or anyone once both players have offered a draw
+    pub authority: Signer<'info>,
+    
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump,
+        close = creator
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    #[account(
+        mut,
+        seeds = [GUESS_LOG_SEED, game_account.key().as_ref()],
+        bump = guess_log.bump,
+        close = opponent
+    )]
+    pub guess_log: Account<'info, GuessLog>,
+    
+    /// CHECK: Must be players[0] recorded on the game
+    #[account(mut, address = game_account.players[0] @ ErrorCode::InvalidPlayerAccount)]
+    pub creator: AccountInfo<'info>,
+    
+    /// CHECK: Must be players[1] recorded on the game
+    #[account(mut, address = game_account.players[1] @ ErrorCode::InvalidPlayerAccount)]
+    pub opponent: AccountInfo<'info>,
+    
+    /// CHECK: Must be the house wallet recorded in config
+    #[account(mut, address = config.house_wallet @ ErrorCode::InvalidHouseWallet)]
+    pub house_wallet: AccountInfo<'info>,
+    
+    /// Token games only: the game's mint (writable so withheld Token-2022 fees can be harvested to it)
+    #[account(mut, constraint = Some(mint.key()) == game_account.mint @ ErrorCode::InvalidTokenAccount)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// Token games only: the game PDA's associated token account
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_account,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the creator's token account for the game's mint
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == game_account.players[0] && Some(creator_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the opponent's token account for the game's mint
+    #[account(
+        mut,
+        constraint = opponent_token_account.owner == game_account.players[1] && Some(opponent_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub opponent_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the house wallet's token account for the game's mint
+    #[account(
+        mut,
+        constraint = house_token_account.owner == config.house_wallet && Some(house_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub house_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireGame<'info> {
+    /// Anyone; receives the crank reward
     #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump,
+        close = creator
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    /// CHECK: Wager and remaining rent go back to the creator
+    #[account(mut, address = game_account.creator @ ErrorCode::InvalidPlayerAccount)]
+    pub creator: AccountInfo<'info>,
+    
+    /// Token games only: the game's mint (writable so withheld Token-2022 fees can be harvested to it)
+    #[account(mut, constraint = Some(mint.key()) == game_account.mint @ ErrorCode::InvalidTokenAccount)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// Token games only: the game PDA's associated token account
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_account,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the creator's token account for the game's mint
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == game_account.players[0] && Some(creator_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimStaleRefund<'info> {
+    /// Either player
+    pub player: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump,
+        close = creator
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    #[account(
+        mut,
+        seeds = [GUESS_LOG_SEED, game_account.key().as_ref()],
+        bump = guess_log.bump,
+        close = opponent
+    )]
+    pub guess_log: Account<'info, GuessLog>,
+    
+    /// CHECK: Must be players[0] recorded on the game
+    #[account(mut, address = game_account.players[0] @ ErrorCode::InvalidPlayerAccount)]
+    pub creator: AccountInfo<'info>,
+    
+    /// CHECK: Must be players[1] recorded on the game
+    #[account(mut, address = game_account.players[1] @ ErrorCode::InvalidPlayerAccount)]
     pub opponent: AccountInfo<'info>,
     
-    /// CHECK: This is the house wallet for fees
+    /// Token games only: the game's mint (writable so withheld Token-2022 fees can be harvested to it)
+    #[account(mut, constraint = Some(mint.key()) == game_account.mint @ ErrorCode::InvalidTokenAccount)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// Token games only: the game PDA's associated token account
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_account,
+        associated_token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the creator's token account for the game's mint
+    #[account(
+        mut,
+        constraint = creator_token_account.owner == game_account.players[0] && Some(creator_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Token games only: the opponent's token account for the game's mint
+    #[account(
+        mut,
+        constraint = opponent_token_account.owner == game_account.players[1] && Some(opponent_token_account.mint) == game_account.mint @ ErrorCode::InvalidTokenAccount
+    )]
+    pub opponent_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseGame<'info> {
+    #[account(
+        mut,
+        seeds = [GAME_SEED, game_account.creator.as_ref(), &game_account.nonce.to_le_bytes()],
+        bump = game_account.bump,
+        close = creator
+    )]
+    pub game_account: Account<'info, GameAccount>,
+    
+    /// CHECK: Rent goes back to the creator who paid it
+    #[account(mut, address = game_account.creator @ ErrorCode::InvalidPlayerAccount)]
+    pub creator: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecoverLegacyGame<'info> {
+    /// CHECK: Legacy-layout game account, validated and closed in the handler
+    #[account(mut, owner = crate::ID)]
+    pub game_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = escrow_account.game == game_account.key() @ ErrorCode::EscrowMismatch,
+        close = creator
+    )]
+    pub escrow_account: Account<'info, EscrowAccount>,
+    
+    /// CHECK: Must be the creator recorded on the legacy game (checked in the handler)
     #[account(mut)]
-    pub house_wallet: AccountInfo<'info>,
+    pub creator: AccountInfo<'info>,
+    
+    /// CHECK: Started games only: the opponent recorded on the legacy game (checked in the handler)
+    #[account(mut)]
+    pub opponent: Option<UncheckedAccount<'info>>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub settlement_authority: Pubkey,
+    pub house_wallet: Pubkey,
+    pub winner_fee_bps: u64,
+    pub forfeit_fee_bps: u64,
+    pub draw_fee_bps: u64, // taken from each player's deposit when a game is drawn
+    pub min_wager: u64,
+    pub max_game_duration: i64, // seconds after join before either player can reclaim their deposit
+    pub round_time_limit: i64, // seconds per round for guesses; should cover the frontend's 5 minute timer plus some grace
+    pub crank_reward: u64, // lamports paid from rent to whoever expires a stale waiting game
+    pub guess_root: [u8; 32], // Merkle root of words accepted as guesses (see wordle_rules::merkle)
+    pub answer_root: [u8; 32], // Merkle root of words that may be the secret word, leaves include the index
+    pub answer_count: u32,
+    pub max_tiebreak_rounds: u8, // sudden-death rounds allowed before the fallback applies
+    pub tiebreak_fallback: BothLostPolicy, // HouseTakesAll or RefundMinusFee
+    pub bump: u8,
+}
+
+// Per-mint settings for token games, set by the admin
+#[account]
+#[derive(InitSpace)]
+pub struct MintConfig {
+    pub mint: Pubkey,
+    pub min_wager: u64, // in the mint's base units
+    pub bump: u8,
+}
+
+impl Config {
+    pub fn apply(&mut self, params: &ConfigParams) {
+        self.settlement_authority = params.settlement_authority;
+        self.house_wallet = params.house_wallet;
+        self.winner_fee_bps = params.winner_fee_bps;
+        self.forfeit_fee_bps = params.forfeit_fee_bps;
+        self.draw_fee_bps = params.draw_fee_bps;
+        self.min_wager = params.min_wager;
+        self.max_game_duration = params.max_game_duration;
+        self.round_time_limit = params.round_time_limit;
+        self.crank_reward = params.crank_reward;
+        self.guess_root = params.guess_root;
+        self.answer_root = params.answer_root;
+        self.answer_count = params.answer_count;
+        self.max_tiebreak_rounds = params.max_tiebreak_rounds;
+        self.tiebreak_fallback = params.tiebreak_fallback;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfigParams {
+    pub settlement_authority: Pubkey,
+    pub house_wallet: Pubkey,
+    pub winner_fee_bps: u64,
+    pub forfeit_fee_bps: u64,
+    pub draw_fee_bps: u64,
+    pub min_wager: u64,
+    pub max_game_duration: i64,
+    pub round_time_limit: i64,
+    pub crank_reward: u64,
+    pub guess_root: [u8; 32],
+    pub answer_root: [u8; 32],
+    pub answer_count: u32,
+    pub max_tiebreak_rounds: u8,
+    pub tiebreak_fallback: BothLostPolicy,
+}
+
+impl ConfigParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.winner_fee_bps <= MAX_FEE_BPS, ErrorCode::InvalidFee);
+        require!(self.forfeit_fee_bps <= MAX_FEE_BPS, ErrorCode::InvalidFee);
+        require!(self.draw_fee_bps <= MAX_FEE_BPS, ErrorCode::InvalidFee);
+        require!(self.min_wager > 0, ErrorCode::InvalidWager);
+        require!(self.max_game_duration > 0, ErrorCode::InvalidDuration);
+        // Timed-out rounds must be settleable before either player can claim a stale refund
+        require!(
+            self.round_time_limit > 0 && self.round_time_limit < self.max_game_duration,
+            ErrorCode::InvalidDuration
+        );
+        require!(self.guess_root != [0; 32] && self.answer_root != [0; 32], ErrorCode::InvalidMerkleRoot);
+        require!(self.answer_count > 0, ErrorCode::InvalidAnswerCount);
+        require!(self.max_tiebreak_rounds <= MAX_TIEBREAK_ROUNDS, ErrorCode::InvalidTiebreakConfig);
+        require!(self.tiebreak_fallback != BothLostPolicy::SuddenDeath, ErrorCode::InvalidTiebreakConfig);
+        Ok(())
+    }
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct GameAccount {
     pub creator: Pubkey,
+    pub nonce: u64,
+    pub bump: u8,
     pub wager_amount: u64,
+    pub mint: Option<Pubkey>, // None for SOL games; amounts are in this mint's base units otherwise
     pub status: GameStatus,
     pub players: [Pubkey; 2],
     pub winner: Pubkey,
     pub created_at: i64,
     pub started_at: i64,
     pub completed_at: i64,
+    pub winner_fee_bps: u64,
+    pub forfeit_fee_bps: u64,
+    pub draw_fee_bps: u64,
+    pub draw_offers: [bool; 2], // set by each player through offer_draw
+    pub both_lost_policy: BothLostPolicy, // chosen by the creator
+    pub round: u8, // 0 for the first round, then one more per tiebreak
+    pub max_tiebreak_rounds: u8, // from config at create time, like the fees
+    pub tiebreak_fallback: BothLostPolicy,
+    pub max_game_duration: i64,
+    pub round_time_limit: i64, // from config at create time; guesses stop and timeouts can settle after it
+    pub join_deadline: Option<i64>,
+    pub crank_reward: u64,
+    pub word_commitment: Option<[u8; 32]>, // sha256(word || salt), set by the settlement authority
+    pub guess_root: [u8; 32], // word list roots from config at create time
+    pub answer_root: [u8; 32],
+    pub answer_count: u32,
+    pub seed_commitments: [[u8; 32]; 2], // sha256(seed) from the creator and the opponent
+    pub seeds: [Option<[u8; 32]>; 2],
+    pub seed_slot: u64, // latest slot hash when the opponent joined
+    pub seed_slot_hash: [u8; 32],
+    pub answer_index: Option<u32>, // derived once both seeds are revealed (see wordle_rules::seed)
+    // What the pot actually received, which is less than the wagers for mints with transfer fees
+    pub total_amount: u64,
+    pub creator_deposited: u64,
+    pub opponent_deposited: u64,
+}
+
+impl GameAccount {
+    // The current round (from started_at, which each tiebreak resets) is past its time limit
+    pub fn round_over(&self, now: i64) -> bool {
+        now >= self.started_at.saturating_add(self.round_time_limit)
+    }
+
+    // Check a revealed word and salt against the commitment stored when play started
+    // The word must also be the answer list entry at the index derived from the players' seeds
+    pub fn verify_word(&self, word: &[u8; WORD_LENGTH], salt: &[u8; 32], answer_proof: &[[u8; 32]]) -> Result<()> {
+        let commitment = self.word_commitment.ok_or(ErrorCode::WordNotCommitted)?;
+        let answer_index = self.answer_index.ok_or(ErrorCode::AnswerNotDerived)?;
+        require!(wordle_rules::is_valid_word(word), ErrorCode::InvalidWord);
+        require!(hashv(&[word, salt]).to_bytes() == commitment, ErrorCode::WordCommitmentMismatch);
+        require!(
+            merkle::verify(&sha256, &self.answer_root, &merkle::answer_leaf_data(answer_index, word), answer_proof),
+            ErrorCode::WordNotInAnswerList
+        );
+        Ok(())
+    }
 }
 
+#[account]
+#[derive(InitSpace)]
+pub struct GuessLog {
+    pub game: Pubkey,
+    pub players: [Pubkey; 2],
+    pub delegates: [Option<Pubkey>; 2],
+    #[max_len(12)] // MAX_GUESSES_PER_PLAYER for each player
+    pub guesses: Vec<GuessEntry>,
+    pub bump: u8,
+}
+
+impl GuessLog {
+    pub fn player_index(&self, player: &Pubkey) -> Result<usize> {
+        self.players
+            .iter()
+            .position(|p| p == player)
+            .ok_or_else(|| error!(ErrorCode::InvalidPlayerAccount))
+    }
+
+    pub fn guess_count(&self, index: usize) -> usize {
+        self.guesses.iter().filter(|g| g.player as usize == index).count()
+    }
+
+    // Replay the log against the revealed word, in submission order
+    pub(crate) fn outcome(&self, word: &[u8; WORD_LENGTH]) -> GuessOutcome {
+        let solves = |g: &&GuessEntry| wordle_rules::is_solved(&wordle_rules::score_guess(&g.word, word));
+        if let Some(entry) = self.guesses.iter().find(solves) {
+            return GuessOutcome::Winner(entry.player as usize);
+        }
+        if (0..2).all(|index| self.guess_count(index) >= MAX_GUESSES_PER_PLAYER) {
+            return GuessOutcome::BothLost;
+        }
+        GuessOutcome::Undecided
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum GuessOutcome {
+    Winner(usize), // index into players
+    BothLost,
+    Undecided,
+}
+
+// The secret word with its commitment salt and answer-list proof, as checked by GameAccount::verify_word
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RevealedWord {
+    pub word: [u8; WORD_LENGTH],
+    pub salt: [u8; 32],
+    pub answer_proof: Vec<[u8; 32]>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct GuessEntry {
+    pub player: u8, // index into players
+    pub word: [u8; WORD_LENGTH],
+    pub slot: u64,
+}
+
+// Game layout from before games were PDAs (keypair accounts with a separate escrow)
+// Only read by recover_legacy_game; shares GameAccount's discriminator
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyGameAccount {
+    pub creator: Pubkey,
+    pub wager_amount: u64,
+    pub status: GameStatus,
+    pub players: [Pubkey; 2],
+    pub winner: Pubkey,
+    pub created_at: i64,
+    pub started_at: i64,
+    pub completed_at: i64,
+}
+
+impl LegacyGameAccount {
+    pub fn try_from_account_info(info: &AccountInfo) -> Result<Self> {
+        let data = info.try_borrow_data()?;
+        require!(data.len() == 8 + Self::INIT_SPACE, ErrorCode::InvalidLegacyGame);
+        require!(data[..8] == GameAccount::DISCRIMINATOR, ErrorCode::InvalidLegacyGame);
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+}
+
+// Legacy per-game escrow (a keypair account), replaced by lamports held on the game account
+// Only read (and closed) by recover_legacy_game
 #[account]
 #[derive(InitSpace)]
 pub struct EscrowAccount {
@@ -361,6 +2041,157 @@ pub enum GameStatus {
     Cancelled,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ForfeitKind {
+    // The player signed their own forfeit
+    Voluntary,
+    // The settlement authority forfeited an abandoned player
+    Abandoned,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum BothLostPolicy {
+    // The whole pot goes to the house
+    HouseTakesAll,
+    // Each player gets their deposit back minus the draw fee
+    RefundMinusFee,
+    // Play tiebreak rounds with a new word (start_tiebreak), then apply the configured fallback
+    SuddenDeath,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum GameOutcome {
+    // A player solved the word and took the pot minus the winner fee
+    Won,
+    // Neither player solved it; the pot was paid out by the game's both-lost policy
+    BothLost,
+}
+
+#[event]
+pub struct GameCreated {
+    pub game: Pubkey,
+    pub creator: Pubkey,
+    pub nonce: u64,
+    pub mint: Option<Pubkey>,
+    pub wager_amount: u64,
+    pub join_deadline: Option<i64>,
+    pub seed_commitment: [u8; 32],
+    pub both_lost_policy: BothLostPolicy,
+    pub created_at: i64,
+}
+
+#[event]
+pub struct GameJoined {
+    pub game: Pubkey,
+    pub opponent: Pubkey,
+    pub total_amount: u64,
+    pub seed_commitment: [u8; 32],
+    pub seed_slot: u64,
+    pub seed_slot_hash: [u8; 32],
+    pub started_at: i64,
+}
+
+#[event]
+pub struct AnswerDerived {
+    pub game: Pubkey,
+    pub seeds: [[u8; 32]; 2],
+    pub seed_slot: u64,
+    pub seed_slot_hash: [u8; 32],
+    pub round: u8,
+    pub answer_index: u32,
+}
+
+#[event]
+pub struct GuessSubmitted {
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub guess: [u8; WORD_LENGTH],
+    pub guess_number: u8,
+    pub slot: u64,
+}
+
+#[event]
+pub struct GameSettled {
+    pub game: Pubkey,
+    pub outcome: GameOutcome,
+    pub winner: Pubkey, // default for BothLost
+    pub word: [u8; WORD_LENGTH],
+    pub winner_amount: u64,
+    pub creator_refund: u64, // non-zero only when a both-lost game refunds deposits minus the draw fee
+    pub opponent_refund: u64,
+    pub fee_amount: u64,
+    pub completed_at: i64,
+}
+
+#[event]
+pub struct GameForfeited {
+    pub game: Pubkey,
+    pub forfeiter: Pubkey,
+    pub winner: Pubkey,
+    pub forfeit_kind: ForfeitKind,
+    pub winner_amount: u64,
+    pub fee_amount: u64,
+    pub completed_at: i64,
+}
+
+#[event]
+pub struct DrawOffered {
+    pub game: Pubkey,
+    pub player: Pubkey,
+    pub agreed: bool, // both players have now offered
+}
+
+#[event]
+pub struct TiebreakStarted {
+    pub game: Pubkey,
+    pub round: u8,
+    pub previous_word: [u8; WORD_LENGTH],
+    // Once AnswerDerived reveals the seeds, previous_answer_index must equal
+    // answer_index(seeds, round_slot_hash(seed_slot_hash, round - 1))
+    pub previous_answer_index: u32,
+    pub seed_slot_hash: [u8; 32],
+    pub word_commitment: [u8; 32],
+    pub started_at: i64,
+}
+
+#[event]
+pub struct GameDrawn {
+    pub game: Pubkey,
+    pub settled_by: Pubkey,
+    pub word: Option<[u8; 5]>, // set when the settlement authority decided the draw (WORD_LENGTH, spelled out for the IDL)
+    pub creator_refund: u64,
+    pub opponent_refund: u64,
+    pub fee_amount: u64,
+    pub completed_at: i64,
+}
+
+#[event]
+pub struct GameRefunded {
+    pub game: Pubkey,
+    pub claimed_by: Pubkey,
+    pub creator_refund: u64,
+    pub opponent_refund: u64,
+    pub refunded_at: i64,
+}
+
+#[event]
+pub struct GameCancelled {
+    pub game: Pubkey,
+    pub creator: Pubkey,
+    pub refund_amount: u64,
+    pub cancelled_at: i64,
+}
+
+#[event]
+pub struct GameExpired {
+    pub game: Pubkey,
+    pub creator: Pubkey,
+    pub cranker: Pubkey,
+    pub refund_amount: u64,
+    pub crank_reward: u64,
+    pub expired_at: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Game is not in waiting status")]
@@ -379,4 +2210,226 @@ pub enum ErrorCode {
     CannotJoinOwnGame,
     #[msg("Unauthorized action")]
     Unauthorized,
+    #[msg("Signer is not the settlement authority")]
+    InvalidSettlementAuthority,
+    #[msg("Fee exceeds 100%")]
+    InvalidFee,
+    #[msg("House wallet does not match config")]
+    InvalidHouseWallet,
+    #[msg("Player account does not match the game's recorded players")]
+    InvalidPlayerAccount,
+    #[msg("Escrow account does not belong to this game")]
+    EscrowMismatch,
+    #[msg("Only the forfeiting player or the settlement authority can forfeit")]
+    UnauthorizedForfeit,
+    #[msg("Account is not a game from before the PDA layout")]
+    InvalidLegacyGame,
+    #[msg("Game is not completed")]
+    GameNotCompleted,
+    #[msg("Duration must be positive")]
+    InvalidDuration,
+    #[msg("Game has not exceeded the maximum game duration")]
+    GameNotStale,
+    #[msg("Join deadline must be in the future")]
+    InvalidJoinDeadline,
+    #[msg("Join deadline has passed")]
+    JoinDeadlinePassed,
+    #[msg("Game has no join deadline")]
+    NoJoinDeadline,
+    #[msg("Join deadline has not passed yet")]
+    JoinDeadlineNotReached,
+    #[msg("Word already committed for this game")]
+    WordAlreadyCommitted,
+    #[msg("No word has been committed for this game")]
+    WordNotCommitted,
+    #[msg("Word must be uppercase A-Z")]
+    InvalidWord,
+    #[msg("Revealed word and salt do not match the commitment")]
+    WordCommitmentMismatch,
+    #[msg("Only the player or their delegate can submit guesses")]
+    UnauthorizedGuess,
+    #[msg("Player has used all 6 guesses")]
+    OutOfGuesses,
+    #[msg("Game account is still open")]
+    GameStillOpen,
+    #[msg("Guess log does not decide the game yet")]
+    GameNotDecided,
+    #[msg("Word list roots must be set")]
+    InvalidMerkleRoot,
+    #[msg("Guess is not in the word list")]
+    NotInWordList,
+    #[msg("Secret word is not in the answer list")]
+    WordNotInAnswerList,
+    #[msg("Answer list must not be empty")]
+    InvalidAnswerCount,
+    #[msg("Seed does not match an unrevealed commitment")]
+    InvalidSeed,
+    #[msg("Both seeds must be revealed before the word can be checked")]
+    AnswerNotDerived,
+    #[msg("SlotHashes sysvar has no entries")]
+    InvalidSlotHashes,
+    #[msg("Token games need the vault, token accounts and token program")]
+    MissingTokenAccounts,
+    #[msg("Token account does not match the game's mint or owner")]
+    InvalidTokenAccount,
+    #[msg("Mint has no mint config")]
+    MintNotConfigured,
+    #[msg("Player has already offered a draw")]
+    DrawAlreadyOffered,
+    #[msg("A draw needs both players' offers or the settlement authority")]
+    DrawNotAgreed,
+    #[msg("Both players lost; start a tiebreak round instead of settling")]
+    TiebreakRequired,
+    #[msg("This game cannot start a tiebreak round")]
+    TiebreakNotAllowed,
+    #[msg("Seeds must stay secret until the final round is settled")]
+    SeedsAlreadyRevealed,
+    #[msg("Tiebreak rounds must be at most MAX_TIEBREAK_ROUNDS with a HouseTakesAll or RefundMinusFee fallback")]
+    InvalidTiebreakConfig,
+    #[msg("Guesses were logged before the word was committed")]
+    GuessesAlreadyLogged,
+    #[msg("The round's time limit has not passed yet")]
+    RoundNotOver,
+    #[msg("A logged guess solved the word; settle from the guess log instead")]
+    GameAlreadyDecided,
+    #[msg("Only the settlement authority can reveal seeds while tiebreak rounds remain")]
+    SeedsLocked,
+    #[msg("The round's time limit has passed")]
+    RoundOver,
+    #[msg("The settlement authority must reveal the word to settle a draw")]
+    WordNotRevealed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(guesses: &[(u8, &str)]) -> GuessLog {
+        GuessLog {
+            game: Pubkey::default(),
+            players: [Pubkey::new_unique(), Pubkey::new_unique()],
+            delegates: [None, None],
+            guesses: guesses
+                .iter()
+                .enumerate()
+                .map(|(slot, &(player, word))| GuessEntry {
+                    player,
+                    word: word.as_bytes().try_into().unwrap(),
+                    slot: slot as u64,
+                })
+                .collect(),
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn first_solve_wins_across_players() {
+        let log = log(&[(0, "SLATE"), (1, "CRANE"), (0, "CRANE")]);
+        assert_eq!(log.outcome(b"CRANE"), GuessOutcome::Winner(1));
+    }
+
+    #[test]
+    fn one_player_out_of_guesses_is_undecided() {
+        let guesses = [(0, "SLATE"); MAX_GUESSES_PER_PLAYER];
+        assert_eq!(log(&guesses).outcome(b"CRANE"), GuessOutcome::Undecided);
+        let mut guesses = guesses.to_vec();
+        guesses.push((1, "SLATE"));
+        assert_eq!(log(&guesses).outcome(b"CRANE"), GuessOutcome::Undecided);
+    }
+
+    #[test]
+    fn both_out_of_guesses_is_both_lost() {
+        let guesses: Vec<_> = (0..2 * MAX_GUESSES_PER_PLAYER).map(|i| ((i % 2) as u8, "SLATE")).collect();
+        assert_eq!(log(&guesses).outcome(b"CRANE"), GuessOutcome::BothLost);
+    }
+
+    #[test]
+    fn last_guess_solve_beats_both_lost() {
+        let mut guesses: Vec<_> = (0..2 * MAX_GUESSES_PER_PLAYER - 1).map(|i| ((i % 2) as u8, "SLATE")).collect();
+        guesses.push((1, "CRANE"));
+        assert_eq!(log(&guesses).outcome(b"CRANE"), GuessOutcome::Winner(1));
+    }
+
+    #[test]
+    fn empty_log_is_undecided() {
+        assert_eq!(log(&[]).outcome(b"CRANE"), GuessOutcome::Undecided);
+    }
+
+    #[test]
+    fn fee_rounds_down_without_overflow() {
+        assert_eq!(fee(1_000, 250), 25);
+        assert_eq!(fee(999, 250), 24);
+        assert_eq!(fee(u64::MAX, MAX_FEE_BPS), u64::MAX);
+        assert_eq!(fee(u64::MAX, 200), u64::MAX / 50);
+        assert_eq!(fee(u64::MAX, 0), 0);
+    }
+
+    // Packed token account data: a Token program account, or a Token-2022 account with `withheld` fees
+    fn token_account_data(amount: u64, withheld: Option<u64>) -> Vec<u8> {
+        use anchor_lang::solana_program::program_pack::Pack;
+        use anchor_spl::token_2022::spl_token_2022::extension::{ExtensionType, StateWithExtensionsMut};
+        use anchor_spl::token_2022::spl_token_2022::state::{Account, AccountState};
+
+        let base = Account {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount,
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        let Some(withheld) = withheld else {
+            let mut data = vec![0; Account::LEN];
+            Account::pack(base, &mut data).unwrap();
+            return data;
+        };
+        let len = ExtensionType::try_calculate_account_len::<Account>(&[ExtensionType::TransferFeeAmount]).unwrap();
+        let mut data = vec![0; len];
+        let mut state = StateWithExtensionsMut::<Account>::unpack_uninitialized(&mut data).unwrap();
+        state.base = base;
+        state.pack_base();
+        state.init_account_type().unwrap();
+        state.init_extension::<TransferFeeAmount>(true).unwrap().withheld_amount = withheld.into();
+        data
+    }
+
+    fn with_account_info<T>(owner: &Pubkey, data: &mut [u8], f: impl FnOnce(&AccountInfo) -> T) -> T {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        f(&AccountInfo::new(&key, false, true, &mut lamports, data, owner, false, 0))
+    }
+
+    #[test]
+    fn received_amount_is_what_reached_the_vault() {
+        assert_eq!(received_amount(500, 1_490).unwrap(), 990);
+        assert_eq!(received_amount(0, 1_000).unwrap(), 1_000);
+        assert!(received_amount(500, 500).is_err());
+        assert!(received_amount(500, 400).is_err());
+    }
+
+    #[test]
+    fn token_2022_balance_and_withheld_fees() {
+        let mut data = token_account_data(990, Some(10));
+        with_account_info(&token_2022::ID, &mut data, |info| {
+            assert_eq!(token_balance(info).unwrap(), 990);
+            assert_eq!(withheld_fees(info).unwrap(), 10);
+        });
+    }
+
+    #[test]
+    fn token_2022_account_without_fee_extension_has_nothing_withheld() {
+        let mut data = token_account_data(1_000, None);
+        with_account_info(&token_2022::ID, &mut data, |info| {
+            assert_eq!(token_balance(info).unwrap(), 1_000);
+            assert_eq!(withheld_fees(info).unwrap(), 0);
+        });
+    }
+
+    #[test]
+    fn token_program_account_has_nothing_withheld() {
+        let mut data = token_account_data(1_000, None);
+        with_account_info(&anchor_spl::token::ID, &mut data, |info| {
+            assert_eq!(token_balance(info).unwrap(), 1_000);
+            assert_eq!(withheld_fees(info).unwrap(), 0);
+        });
+    }
 }
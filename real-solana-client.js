@@ -4,14 +4,46 @@
  */
 
 const { Connection, PublicKey, Transaction, SystemProgram, LAMPORTS_PER_SOL, sendAndConfirmTransaction, Keypair, TransactionInstruction } = require('@solana/web3.js');
-const { AnchorProvider, Program, Wallet } = require('@coral-xyz/anchor');
+const { AnchorProvider, Program, Wallet, BN } = require('@coral-xyz/anchor');
 const fs = require('fs');
 const path = require('path');
+const crypto = require('crypto');
 
 // Load the IDL
 const idlPath = path.join(__dirname, 'wordle-escrow-idl.json');
 const idl = JSON.parse(fs.readFileSync(idlPath, 'utf8'));
 
+// Merkle proofs from dictionary/*.json are hex strings; the program takes 32-byte arrays
+function proofBytes(proof) {
+    return proof.map(hash => Array.from(Buffer.from(hash, 'hex')));
+}
+
+function sha256(...parts) {
+    const hash = crypto.createHash('sha256');
+    parts.forEach(part => hash.update(Buffer.from(part)));
+    return hash.digest();
+}
+
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey('ATokenGPvbd4ZjGt9xVj7GVMBiAzbbR2EPL95bg2ZuF');
+
+// Associated token accounts depend on the mint's token program (Token or Token-2022)
+function associatedTokenAddress(owner, mint, tokenProgram) {
+    const [address] = PublicKey.findProgramAddressSync(
+        [owner.toBuffer(), tokenProgram.toBuffer(), mint.toBuffer()],
+        ASSOCIATED_TOKEN_PROGRAM_ID
+    );
+    return address;
+}
+
+// Mirrors wordle_rules::seed::answer_index: sha256(creator seed || opponent seed || slot hash || answer root),
+// first 8 bytes as a little-endian u64, modulo the answer count
+// Tiebreak rounds use sha256(slot hash || round) as the slot hash (wordle_rules::seed::round_slot_hash)
+function answerIndex(seeds, slotHash, answerRoot, answerCount, round = 0) {
+    const roundSlotHash = round === 0 ? slotHash : sha256(slotHash, Buffer.from([round]));
+    const digest = sha256(seeds[0], seeds[1], roundSlotHash, answerRoot);
+    return Number(digest.readBigUInt64LE(0) % BigInt(answerCount));
+}
+
 class RealSolanaGameClient {
     constructor() {
         // Use Solana devnet for testing
@@ -58,6 +90,114 @@ class RealSolanaGameClient {
         return new Program(idl, this.programId, provider);
     }
 
+    /**
+     * Derive the game PDA for a creator's game nonce
+     * Seeds mirror the program: ["game", creator, nonce (u64 LE)]
+     * The game account also holds the staked lamports
+     */
+    deriveGameAddress(creatorAddress, nonce) {
+        const creator = new PublicKey(creatorAddress);
+        const [gameAccount] = PublicKey.findProgramAddressSync(
+            [Buffer.from('game'), creator.toBuffer(), new BN(nonce).toArrayLike(Buffer, 'le', 8)],
+            this.programId
+        );
+        return gameAccount;
+    }
+
+    /**
+     * Derive the guess log PDA for a game: ["guess_log", game]
+     * Created by join_game; players delegate guess submission to the backend authority
+     */
+    deriveGuessLogAddress(gameAccount) {
+        const [guessLog] = PublicKey.findProgramAddressSync(
+            [Buffer.from('guess_log'), new PublicKey(gameAccount).toBuffer()],
+            this.programId
+        );
+        return guessLog;
+    }
+
+    /**
+     * When the game's current round passes its on-chain round_time_limit, in ms since the epoch
+     * From then on guesses are rejected and settle_game accepts the round as timed out
+     */
+    async roundDeadline(escrowDetails) {
+        try {
+            const program = this.createProgram(this.createProvider(new Wallet(this.authorityKeypair)));
+            const game = await program.account.gameAccount.fetch(new PublicKey(escrowDetails.gameAccount));
+            return { success: true, deadline: (game.startedAt.toNumber() + game.roundTimeLimit.toNumber()) * 1000 };
+        } catch (error) {
+            console.error('❌ Round deadline lookup failed:', error);
+            return { success: false, error: error.message };
+        }
+    }
+
+    /**
+     * Derive the answer index from both players' seeds, the same way reveal_seed does on-chain
+     * `seeds` are the creator's and opponent's 32-byte seeds; each must match its on-chain commitment
+     * `round` defaults to the game's current round (tiebreak rounds get a new answer)
+     */
+    async deriveAnswerIndex(escrowDetails, seeds, round = null) {
+        try {
+            const program = this.createProgram(this.createProvider(new Wallet(this.authorityKeypair)));
+            const game = await program.account.gameAccount.fetch(new PublicKey(escrowDetails.gameAccount));
+            for (let i = 0; i < 2; i++) {
+                if (!sha256(seeds[i]).equals(Buffer.from(game.seedCommitments[i]))) {
+                    return { success: false, error: `Seed ${i} does not match its on-chain commitment` };
+                }
+            }
+            const index = answerIndex(seeds, game.seedSlotHash, game.answerRoot, game.answerCount, round ?? game.round);
+            return { success: true, answerIndex: index };
+        } catch (error) {
+            console.error('❌ Answer derivation failed:', error);
+            return { success: false, error: error.message };
+        }
+    }
+
+    /**
+     * reveal_seed instructions for any seeds not yet revealed on-chain
+     * Sent ahead of settlement, which needs the answer index derived from both seeds
+     */
+    async revealSeedInstructions(program, gameAccount, seeds) {
+        const game = await program.account.gameAccount.fetch(gameAccount);
+        const [configPubkey] = PublicKey.findProgramAddressSync([Buffer.from('config')], this.programId);
+        const instructions = [];
+        for (let i = 0; i < 2; i++) {
+            if (game.seeds[i]) {
+                continue;
+            }
+            instructions.push(await program.methods
+                .revealSeed(Array.from(seeds[i]))
+                .accounts({
+                    revealer: this.authorityKeypair.publicKey,
+                    config: configPubkey,
+                    gameAccount: gameAccount,
+                })
+                .instruction());
+        }
+        return instructions;
+    }
+
+    /**
+     * Extra accounts settlement needs for token games: the mint, the game's vault and each payee's
+     * associated token account for the mint. SOL games (no mint) need none.
+     */
+    async tokenPayoutAccounts(program, gameAccount, players, houseWallet) {
+        const game = await program.account.gameAccount.fetch(gameAccount);
+        if (!game.mint) {
+            return {};
+        }
+        const mintInfo = await this.connection.getAccountInfo(game.mint);
+        const tokenProgram = mintInfo.owner;
+        return {
+            mint: game.mint,
+            vault: associatedTokenAddress(gameAccount, game.mint, tokenProgram),
+            creatorTokenAccount: associatedTokenAddress(new PublicKey(players[0]), game.mint, tokenProgram),
+            opponentTokenAccount: associatedTokenAddress(new PublicKey(players[1]), game.mint, tokenProgram),
+            houseTokenAccount: associatedTokenAddress(houseWallet, game.mint, tokenProgram),
+            tokenProgram,
+        };
+    }
+
     /**
      * Request devnet SOL airdrop for testing
      */
@@ -99,7 +239,7 @@ class RealSolanaGameClient {
      * Create a REAL blockchain game using the deployed Anchor program
      * This creates actual on-chain accounts and requires real SOL transfers
      */
-    async createGameEscrow(playerAddress, wagerAmount) {
+    async createGameEscrow(playerAddress, wagerAmount, bothLostPolicy = 'houseTakesAll') {
         try {
             console.log(`🎮 Creating REAL blockchain game for ${playerAddress} with ${wagerAmount} SOL`);
             
@@ -116,27 +256,30 @@ class RealSolanaGameClient {
             const gameId = `game_${Date.now()}_${Math.random().toString(36).substr(2, 9)}`;
             const escrowId = `escrow_${Date.now()}_${Math.random().toString(36).substr(2, 9)}`;
             
-            // Generate keypairs for the game and escrow accounts
-            const gameAccount = Keypair.generate();
-            const escrowAccount = Keypair.generate();
+            // The game account is a PDA derived from the creator's wallet and a nonce
+            const nonce = Date.now().toString();
+            const gameAccount = this.deriveGameAddress(playerAddress, nonce);
             
-            // For now, we'll use a simple escrow approach
-            // The escrow account will receive the SOL transfer directly
+            // Matches the server's 30 minute lobby cleanup; after this anyone can expire_game on-chain
+            const joinDeadline = Math.floor(Date.now() / 1000) + 30 * 60;
             
             // Convert wager to lamports
             const wagerLamports = Math.floor(wagerAmount * LAMPORTS_PER_SOL);
             
             console.log(`✅ Real blockchain game prepared: ${escrowId}`);
-            console.log(`📍 Game Account: ${gameAccount.publicKey.toString()}`);
-            console.log(`📍 Escrow Account: ${escrowAccount.publicKey.toString()}`);
+            console.log(`📍 Game Account: ${gameAccount.toString()}`);
             console.log(`💰 Wager Amount: ${wagerLamports} lamports (${wagerAmount} SOL)`);
             
             // Return transaction details for frontend to sign
             return { 
                 success: true, 
                 escrowId,
-                gameAccount: gameAccount.publicKey.toString(),
-                escrowAccount: escrowAccount.publicKey.toString(),
+                gameAccount: gameAccount.toString(),
+                guessLog: this.deriveGuessLogAddress(gameAccount).toString(),
+                guessDelegate: this.authorityKeypair.publicKey.toString(),
+                nonce,
+                joinDeadline,
+                bothLostPolicy,
                 wagerAmount,
                 wagerLamports,
                 transferAmount: wagerAmount, // Add this for the frontend
@@ -145,14 +288,6 @@ class RealSolanaGameClient {
                 // Real Anchor program details
                 escrowType: 'real_anchor_program',
                 programId: this.programId.toString(),
-                gameKeypair: {
-                    publicKey: gameAccount.publicKey.toString(),
-                    secretKey: Array.from(gameAccount.secretKey)
-                },
-                escrowKeypair: {
-                    publicKey: escrowAccount.publicKey.toString(),
-                    secretKey: Array.from(escrowAccount.secretKey)
-                },
                 // Transaction instruction data for frontend
                 instructionData: {
                     discriminator: Buffer.from([51, 230, 133, 164, 1, 127, 131, 173]), // create_game discriminator
@@ -194,14 +329,130 @@ class RealSolanaGameClient {
     }
 
     /**
-     * Settle game - distribute winnings with fees
+     * Record a guess in the on-chain guess log
+     * Signed by the backend authority, which each player sets as their guess delegate
+     * `proof` shows the guess is in the guess dictionary (dictionary/guesses.json)
+     * Safe to retry: succeeds without sending if the player already has `guessNumber` guesses logged
      */
-    async settleGame(escrowDetails, players, winner, isForfeit, bothLost) {
+    async submitGuess(escrowDetails, playerAddress, guess, proof, guessNumber) {
         try {
-            console.log(`🏆 Settling game on smart contract:`, {
-                winner: winner || 'house',
-                isForfeit,
-                bothLost,
+            const program = this.createProgram(this.createProvider(new Wallet(this.authorityKeypair)));
+            const gameAccount = new PublicKey(escrowDetails.gameAccount);
+            const player = new PublicKey(playerAddress);
+            
+            const guessLog = await program.account.guessLog.fetch(this.deriveGuessLogAddress(gameAccount));
+            const index = guessLog.players.findIndex(p => p.equals(player));
+            if (guessLog.guesses.filter(g => g.player === index).length >= guessNumber) {
+                return { success: true, signature: null };
+            }
+            
+            const signature = await program.methods
+                .submitGuess(player, Array.from(Buffer.from(guess, 'ascii')), proofBytes(proof))
+                .accounts({
+                    signer: this.authorityKeypair.publicKey,
+                    gameAccount: gameAccount,
+                    guessLog: this.deriveGuessLogAddress(gameAccount),
+                })
+                .rpc();
+            
+            console.log('📝 Guess logged on blockchain:', signature);
+            return { success: true, signature: signature };
+        } catch (error) {
+            console.error('❌ Guess log failed:', error);
+            return { success: false, error: error.message };
+        }
+    }
+
+    /**
+     * Commit to the secret word once play starts: sha256(word || salt)
+     * The word and salt are revealed to the program at settlement
+     * Safe to retry: succeeds without sending if the same commitment is already on-chain
+     */
+    async commitWord(escrowDetails, word, salt) {
+        try {
+            const program = this.createProgram(this.createProvider(new Wallet(this.authorityKeypair)));
+            const gameAccount = new PublicKey(escrowDetails.gameAccount);
+            const [configPubkey] = PublicKey.findProgramAddressSync([Buffer.from('config')], this.programId);
+            const commitment = crypto.createHash('sha256')
+                .update(Buffer.from(word, 'ascii'))
+                .update(salt)
+                .digest();
+            
+            const game = await program.account.gameAccount.fetch(gameAccount);
+            if (game.wordCommitment && Buffer.from(game.wordCommitment).equals(commitment)) {
+                return { success: true, signature: null };
+            }
+            
+            const signature = await program.methods
+                .commitWord(Array.from(commitment))
+                .accounts({
+                    settlementAuthority: this.authorityKeypair.publicKey,
+                    config: configPubkey,
+                    gameAccount: gameAccount,
+                    guessLog: this.deriveGuessLogAddress(gameAccount),
+                })
+                .rpc();
+            
+            console.log('🔒 Word committed on blockchain:', signature);
+            return { success: true, signature: signature };
+        } catch (error) {
+            console.error('❌ Word commit failed:', error);
+            return { success: false, error: error.message };
+        }
+    }
+
+    /**
+     * Start a sudden-death tiebreak round after both players failed
+     * Reveals the lost word and commits to the next one, which must be the answer for the next round
+     * (deriveAnswerIndex with round + 1)
+     * `answerProof` shows the lost word sits in the answer list at `answerIndex`; the seeds stay secret,
+     * so that index is only checked against them once they are revealed (TiebreakStarted)
+     */
+    async startTiebreak(escrowDetails, word, salt, answerIndex, answerProof, nextWord, nextSalt) {
+        try {
+            const program = this.createProgram(this.createProvider(new Wallet(this.authorityKeypair)));
+            const gameAccount = new PublicKey(escrowDetails.gameAccount);
+            const [configPubkey] = PublicKey.findProgramAddressSync([Buffer.from('config')], this.programId);
+            const commitment = crypto.createHash('sha256')
+                .update(Buffer.from(nextWord, 'ascii'))
+                .update(nextSalt)
+                .digest();
+            
+            const signature = await program.methods
+                .startTiebreak(
+                    Array.from(Buffer.from(word, 'ascii')),
+                    Array.from(salt),
+                    answerIndex,
+                    proofBytes(answerProof),
+                    Array.from(commitment)
+                )
+                .accounts({
+                    settlementAuthority: this.authorityKeypair.publicKey,
+                    config: configPubkey,
+                    gameAccount: gameAccount,
+                    guessLog: this.deriveGuessLogAddress(gameAccount),
+                })
+                .rpc();
+            
+            console.log('⚔️ Tiebreak round started on blockchain:', signature);
+            return { success: true, signature: signature };
+        } catch (error) {
+            console.error('❌ Tiebreak start failed:', error);
+            return { success: false, error: error.message };
+        }
+    }
+
+    /**
+     * Settle a round nobody solved in time - both players lose and the game's both-lost policy pays out
+     * The program only accepts this once the game's round_time_limit has passed since the round started and
+     * the guess log holds no solve; guess-decided games go through settleVerified
+     * Reveals the committed word and salt so the program can check them,
+     * with `answerProof` showing the word is in the answer list (dictionary/answers.json)
+     * at the index derived from the players' `seeds`, which are revealed first
+     */
+    async settleGame(escrowDetails, players, word, salt, answerProof, seeds) {
+        try {
+            console.log(`🏆 Settling timed-out game on smart contract:`, {
                 players,
                 escrowDetails
             });
@@ -232,32 +483,40 @@ class RealSolanaGameClient {
             
             // Get account public keys
             const gameAccount = new PublicKey(escrowDetails.gameAccount);
-            const escrowAccount = new PublicKey(escrowDetails.escrowAccount);
             const creatorPubkey = new PublicKey(players[0]);
             const opponentPubkey = new PublicKey(players[1]);
-            const houseWalletPubkey = new PublicKey('FRG1E6NiJ9UVN4T4v2r9hN1JzqB9r1uPuetCLXuqiRjT');
-            const winnerPubkey = winner ? new PublicKey(winner) : houseWalletPubkey;
+            const [configPubkey] = PublicKey.findProgramAddressSync([Buffer.from('config')], this.programId);
+            // The program rejects any fee recipient other than the configured house wallet
+            const config = await program.account.config.fetch(configPubkey);
+            const houseWalletPubkey = config.houseWallet;
             
             console.log('📝 Calling settle_game on smart contract...');
             console.log('   Game Account:', gameAccount.toString());
-            console.log('   Escrow Account:', escrowAccount.toString());
-            console.log('   Winner:', winnerPubkey.toString());
             console.log('   Creator:', creatorPubkey.toString());
             console.log('   Opponent:', opponentPubkey.toString());
             console.log('   House:', houseWalletPubkey.toString());
-            console.log('   Is Forfeit:', isForfeit);
-            console.log('   Both Lost:', bothLost);
+            
+            const revealIxs = await this.revealSeedInstructions(program, gameAccount, seeds);
+            const tokenAccounts = await this.tokenPayoutAccounts(program, gameAccount, players, houseWalletPubkey);
             
             // Build transaction
             const tx = await program.methods
-                .settleGame(winnerPubkey, isForfeit, bothLost)
+                .settleGame(
+                    Array.from(Buffer.from(word, 'ascii')),
+                    Array.from(salt),
+                    proofBytes(answerProof)
+                )
                 .accounts({
+                    settlementAuthority: this.authorityKeypair.publicKey,
+                    config: configPubkey,
                     gameAccount: gameAccount,
-                    escrowAccount: escrowAccount,
+                    guessLog: this.deriveGuessLogAddress(gameAccount),
                     creator: creatorPubkey,
                     opponent: opponentPubkey,
                     houseWallet: houseWalletPubkey,
+                    ...tokenAccounts,
                 })
+                .preInstructions(revealIxs)
                 .transaction();
             
             // Get fresh blockhash
@@ -293,6 +552,42 @@ class RealSolanaGameClient {
         }
     }
 
+    /**
+     * Settle from the on-chain guess log - the program picks the winner itself
+     * Only reveals the seeds, word and salt; fails with GameNotDecided if the log doesn't settle the game
+     */
+    async settleVerified(escrowDetails, players, word, salt, answerProof, seeds) {
+        try {
+            const program = this.createProgram(this.createProvider(new Wallet(this.authorityKeypair)));
+            const gameAccount = new PublicKey(escrowDetails.gameAccount);
+            const [configPubkey] = PublicKey.findProgramAddressSync([Buffer.from('config')], this.programId);
+            const config = await program.account.config.fetch(configPubkey);
+            const revealIxs = await this.revealSeedInstructions(program, gameAccount, seeds);
+            const tokenAccounts = await this.tokenPayoutAccounts(program, gameAccount, players, config.houseWallet);
+            
+            const signature = await program.methods
+                .settleVerified(Array.from(Buffer.from(word, 'ascii')), Array.from(salt), proofBytes(answerProof))
+                .accounts({
+                    settlementAuthority: this.authorityKeypair.publicKey,
+                    config: configPubkey,
+                    gameAccount: gameAccount,
+                    guessLog: this.deriveGuessLogAddress(gameAccount),
+                    creator: new PublicKey(players[0]),
+                    opponent: new PublicKey(players[1]),
+                    houseWallet: config.houseWallet,
+                    ...tokenAccounts,
+                })
+                .preInstructions(revealIxs)
+                .rpc();
+            
+            console.log('✅ Game settled from guess log:', signature);
+            return { success: true, signature: signature };
+        } catch (error) {
+            console.error('❌ Verified settlement failed:', error);
+            return { success: false, error: error.message };
+        }
+    }
+
     /**
      * Cancel game - NOTE: Cancellation happens on-chain via frontend
      * Backend only removes game from active list
@@ -312,3 +607,4 @@ class RealSolanaGameClient {
 }
 
 module.exports = RealSolanaGameClient;
+module.exports.answerIndex = answerIndex;
@@ -2,31 +2,95 @@ const express = require('express');
 const http = require('http');
 const socketIo = require('socket.io');
 const cors = require('cors');
+const crypto = require('crypto');
 const RealSolanaGameClient = require('./real-solana-client');
 
-// House wallet for fees and both-lose scenarios
-const HOUSE_WALLET = 'FRG1E6NiJ9UVN4T4v2r9hN1JzqB9r1uPuetCLXuqiRjT';
+// Salts for on-chain word commitments, keyed by game id
+// Kept out of the game object so they are never broadcast to clients
+const wordSalts = new Map();
+
+// Players' secret seeds, keyed by game id as [creator, opponent]
+// The word is derived from both once the opponent joins; they are revealed on-chain at settlement
+const gameSeeds = new Map();
+
+function parseSeed(seed) {
+    return typeof seed === 'string' && /^[0-9a-f]{64}$/i.test(seed) ? Buffer.from(seed, 'hex') : null;
+}
+
+// Slack on top of the on-chain round deadline, since the cluster clock can trail wall-clock time
+const CLOCK_SLACK_MS = 5000;
+
+// Retry an on-chain write a few times before giving up; returns the last result
+async function withRetries(action, attempts = 3) {
+    let result;
+    for (let attempt = 1; attempt <= attempts; attempt++) {
+        result = await action();
+        if (result.success) {
+            return result;
+        }
+        console.warn(`⚠️ Attempt ${attempt}/${attempts} failed: ${result.error}`);
+        if (attempt < attempts) {
+            await new Promise(resolve => setTimeout(resolve, 1000 * attempt));
+        }
+    }
+    return result;
+}
 
 // Helper function to settle game on blockchain
-async function settleGameOnBlockchain(game, winner, isForfeit, bothLost) {
+// Games decided by guesses settle from the on-chain guess log, so the program picks the winner;
+// timeouts go through settle_game, which pays out as both lost once the round's time limit has passed
+async function settleGameOnBlockchain(game, verified = false) {
     try {
         console.log(`💰 Settling game ${game.id} on blockchain:`, {
-            winner: winner || 'none',
-            isForfeit,
-            bothLost,
+            verified,
             escrowDetails: game.escrowDetails
         });
         
+        if (verified) {
+            const result = await solanaClient.settleVerified(
+                game.escrowDetails,
+                game.players,
+                game.word,
+                wordSalts.get(game.id),
+                answerDictionary.proofs[game.word],
+                gameSeeds.get(game.id)
+            );
+            if (result.success) {
+                wordSalts.delete(game.id);
+                gameSeeds.delete(game.id);
+                console.log(`✅ Game settled from guess log: ${result.signature}`);
+            } else {
+                console.error(`❌ Verified settlement failed: ${result.error}`);
+            }
+            return result;
+        }
+        
+        // Players can time out before the game's on-chain round limit (their timers start after started_at),
+        // so wait it out
+        const deadline = await withRetries(() => solanaClient.roundDeadline(game.escrowDetails));
+        if (!deadline.success) {
+            console.error(`❌ Blockchain settlement failed: ${deadline.error}`);
+            return deadline;
+        }
+        const remaining = deadline.deadline + CLOCK_SLACK_MS - Date.now();
+        if (remaining > 0) {
+            await new Promise(resolve => setTimeout(resolve, remaining));
+        }
+        
         // Call smart contract settle_game instruction via the Solana client
+        // The word and salt must match the commitment made when play started
         const result = await solanaClient.settleGame(
             game.escrowDetails,
             game.players,
-            winner || HOUSE_WALLET,
-            isForfeit,
-            bothLost
+            game.word,
+            wordSalts.get(game.id),
+            answerDictionary.proofs[game.word],
+            gameSeeds.get(game.id)
         );
         
         if (result.success) {
+            wordSalts.delete(game.id);
+            gameSeeds.delete(game.id);
             console.log(`✅ Game settled on blockchain: ${result.signature}`);
         } else {
             console.error(`❌ Blockchain settlement failed: ${result.error}`);
@@ -111,6 +175,9 @@ function validateWalletAddress(address) {
     return solanaAddressRegex.test(address);
 }
 
+// Both-lost policies the server can settle; sudden-death tiebreaks are not run by the server yet
+const BOTH_LOST_POLICIES = ['houseTakesAll', 'refundMinusFee'];
+
 function validateWager(wager) {
     if (typeof wager === 'string') {
         wager = parseFloat(wager);
@@ -146,64 +213,11 @@ let players = new Map(); // socketId -> player info
 let socketIdToWallet = new Map();
 let connectedWallets = new Set();
 
-// Word list (same as frontend)
-const words = [
-    'ABOUT', 'ABOVE', 'ABUSE', 'ACTOR', 'ACUTE', 'ADMIT', 'ADOPT', 'ADULT', 'AFTER', 'AGAIN',
-    'AGENT', 'AGREE', 'AHEAD', 'ALARM', 'ALBUM', 'ALERT', 'ALIEN', 'ALIGN', 'ALIKE', 'ALIVE',
-    'ALLOW', 'ALONE', 'ALONG', 'ALTER', 'AMONG', 'ANGER', 'ANGLE', 'ANGRY', 'APART', 'APPLE',
-    'APPLY', 'ARENA', 'ARGUE', 'ARISE', 'ARRAY', 'ASIDE', 'ASSET', 'AVOID', 'AWAKE', 'AWARD',
-    'AWARE', 'BADLY', 'BAKER', 'BASES', 'BASIC', 'BEACH', 'BEGAN', 'BEGIN', 'BEING', 'BELOW',
-    'BENCH', 'BILLY', 'BIRTH', 'BLACK', 'BLAME', 'BLANK', 'BLIND', 'BLOCK', 'BLOOD', 'BOARD',
-    'BOOST', 'BOOTH', 'BOUND', 'BRAIN', 'BRAND', 'BRASS', 'BRAVE', 'BREAD', 'BREAK', 'BREED',
-    'BRIEF', 'BRING', 'BROAD', 'BROKE', 'BROWN', 'BUILD', 'BUILT', 'BUYER', 'CABLE', 'CALIF',
-    'CARRY', 'CATCH', 'CAUSE', 'CHAIN', 'CHAIR', 'CHAOS', 'CHARM', 'CHART', 'CHASE', 'CHEAP',
-    'CHECK', 'CHEST', 'CHIEF', 'CHILD', 'CHINA', 'CHOSE', 'CIVIL', 'CLAIM', 'CLASS', 'CLEAN',
-    'CLEAR', 'CLICK', 'CLIMB', 'CLOCK', 'CLOSE', 'CLOUD', 'COACH', 'COAST', 'COULD', 'COUNT',
-    'COURT', 'COVER', 'CRAFT', 'CRASH', 'CRAZY', 'CREAM', 'CRIME', 'CROSS', 'CROWD', 'CROWN',
-    'CRUDE', 'CURVE', 'CYCLE', 'DAILY', 'DANCE', 'DATED', 'DEALT', 'DEATH', 'DEBUT', 'DELAY',
-    'DEPTH', 'DOING', 'DOUBT', 'DOZEN', 'DRAFT', 'DRAMA', 'DRANK', 'DRAWN', 'DREAM', 'DRESS',
-    'DRILL', 'DRINK', 'DRIVE', 'DROVE', 'DYING', 'EAGER', 'EARLY', 'EARTH', 'EIGHT', 'ELITE',
-    'EMPTY', 'ENEMY', 'ENJOY', 'ENTER', 'ENTRY', 'EQUAL', 'ERROR', 'EVENT', 'EVERY', 'EXACT',
-    'EXIST', 'EXTRA', 'FAITH', 'FALSE', 'FAULT', 'FIBER', 'FIELD', 'FIFTH', 'FIFTY', 'FIGHT',
-    'FINAL', 'FIRST', 'FIXED', 'FLASH', 'FLEET', 'FLOOR', 'FLUID', 'FOCUS', 'FORCE', 'FORTH',
-    'FORTY', 'FORUM', 'FOUND', 'FRAME', 'FRANK', 'FRAUD', 'FRESH', 'FRONT', 'FROST', 'FRUIT',
-    'FULLY', 'FUNNY', 'GIANT', 'GIVEN', 'GLASS', 'GLOBE', 'GOING', 'GRACE', 'GRADE', 'GRAND',
-    'GRANT', 'GRASS', 'GRAVE', 'GREAT', 'GREEN', 'GROSS', 'GROUP', 'GROWN', 'GUARD', 'GUESS',
-    'GUEST', 'GUIDE', 'HAPPY', 'HARRY', 'HEART', 'HEAVY', 'HORSE', 'HOTEL', 'HOUSE', 'HUMAN',
-    'IDEAL', 'IMAGE', 'INDEX', 'INNER', 'INPUT', 'ISSUE', 'JAPAN', 'JIMMY', 'JOINT', 'JONES',
-    'JUDGE', 'KNOWN', 'LABEL', 'LARGE', 'LASER', 'LATER', 'LAUGH', 'LAYER', 'LEARN', 'LEASE',
-    'LEAST', 'LEAVE', 'LEGAL', 'LEVEL', 'LEWIS', 'LIGHT', 'LIMIT', 'LINKS', 'LIVES', 'LOCAL',
-    'LOOSE', 'LOWER', 'LUCKY', 'LUNCH', 'LYING', 'MAGIC', 'MAJOR', 'MAKER', 'MARCH', 'MARIA',
-    'MATCH', 'MAYBE', 'MAYOR', 'MEANT', 'MEDIA', 'METAL', 'MIGHT', 'MINOR', 'MINUS', 'MIXED',
-    'MODEL', 'MONEY', 'MONTH', 'MORAL', 'MOTOR', 'MOUNT', 'MOUSE', 'MOUTH', 'MOVED', 'MOVIE',
-    'MUSIC', 'NEEDS', 'NEVER', 'NEWLY', 'NIGHT', 'NOISE', 'NORTH', 'NOTED', 'NOVEL', 'NURSE',
-    'OCCUR', 'OCEAN', 'OFFER', 'OFTEN', 'ORDER', 'OTHER', 'OUGHT', 'PAINT', 'PANEL', 'PAPER',
-    'PARTY', 'PEACE', 'PETER', 'PHASE', 'PHONE', 'PHOTO', 'PIANO', 'PIECE', 'PILOT', 'PITCH',
-    'PLACE', 'PLAIN', 'PLANE', 'PLANT', 'PLATE', 'PLAZA', 'PLOT', 'PLUG', 'PLUS', 'POINT',
-    'POUND', 'POWER', 'PRESS', 'PRICE', 'PRIDE', 'PRIME', 'PRINT', 'PRIOR', 'PRIZE', 'PROOF',
-    'PROUD', 'PROVE', 'QUEEN', 'QUICK', 'QUIET', 'QUITE', 'RADIO', 'RAISE', 'RANGE', 'RAPID',
-    'RATIO', 'REACH', 'READY', 'REALM', 'REBEL', 'REFER', 'RELAX', 'REPAY', 'REPLY', 'RIGHT',
-    'RIGID', 'RIVER', 'ROBIN', 'ROGER', 'ROMAN', 'ROUGH', 'ROUND', 'ROUTE', 'ROYAL', 'RURAL',
-    'SCALE', 'SCENE', 'SCOPE', 'SCORE', 'SENSE', 'SERVE', 'SETUP', 'SEVEN', 'SHALL', 'SHAPE',
-    'SHARE', 'SHARP', 'SHEET', 'SHELF', 'SHELL', 'SHIFT', 'SHINE', 'SHIRT', 'SHOCK', 'SHOOT',
-    'SHORT', 'SHOWN', 'SIDED', 'SIGHT', 'SILLY', 'SINCE', 'SIXTY', 'SIZED', 'SKILL', 'SLEEP',
-    'SLIDE', 'SMALL', 'SMART', 'SMILE', 'SMITH', 'SMOKE', 'SNAKE', 'SNOW', 'SOLAR', 'SOLID',
-    'SOLVE', 'SORRY', 'SOUND', 'SOUTH', 'SPACE', 'SPARE', 'SPEAK', 'SPEED', 'SPEND', 'SPENT',
-    'SPLIT', 'SPOKE', 'SPORT', 'STAFF', 'STAGE', 'STAKE', 'STAND', 'START', 'STATE', 'STEAM',
-    'STEEL', 'STEEP', 'STEER', 'STEPS', 'STICK', 'STILL', 'STOCK', 'STONE', 'STOOD', 'STORE',
-    'STORM', 'STORY', 'STRIP', 'STUCK', 'STUDY', 'STUFF', 'STYLE', 'SUGAR', 'SUITE', 'SUPER',
-    'SWEET', 'TABLE', 'TAKEN', 'TASTE', 'TAXES', 'TEACH', 'TEETH', 'TERRY', 'TEXAS', 'THANK',
-    'THEFT', 'THEIR', 'THEME', 'THERE', 'THESE', 'THICK', 'THING', 'THINK', 'THIRD', 'THOSE',
-    'THREE', 'THREW', 'THROW', 'THUMB', 'TIGHT', 'TIMER', 'TIMES', 'TITLE', 'TODAY', 'TOKEN',
-    'TOMMY', 'TOOLS', 'TOPIC', 'TOTAL', 'TOUCH', 'TOUGH', 'TOWER', 'TRACK', 'TRADE', 'TRAIN',
-    'TREAT', 'TREND', 'TRIAL', 'TRIBE', 'TRICK', 'TRIED', 'TRIES', 'TRUCK', 'TRULY', 'TRUNK',
-    'TRUST', 'TRUTH', 'TWICE', 'TWIST', 'TYLER', 'TYPES', 'UNCLE', 'UNDER', 'UNDUE', 'UNION',
-    'UNITY', 'UNTIL', 'UPPER', 'UPSET', 'URBAN', 'USAGE', 'USUAL', 'VALID', 'VALUE', 'VIDEO',
-    'VIRUS', 'VISIT', 'VITAL', 'VOCAL', 'WASTE', 'WATCH', 'WATER', 'WAVES', 'WAYS', 'WEIRD',
-    'WELSH', 'WHEEL', 'WHERE', 'WHICH', 'WHILE', 'WHITE', 'WHOLE', 'WHOSE', 'WOMAN', 'WOMEN',
-    'WORLD', 'WORRY', 'WORSE', 'WORST', 'WORTH', 'WOULD', 'WRITE', 'WRONG', 'WROTE', 'YOUNG',
-    'YOUTH'
-];
+// Word lists with Merkle proofs, built from dictionary/*.txt by crates/wordle-dictionary
+// The program only accepts guesses and secret words that prove against the roots in its config
+const answerDictionary = require('./dictionary/answers.json');
+const guessDictionary = require('./dictionary/guesses.json');
+const words = Object.keys(answerDictionary.proofs);
 
 // Routes
 app.get('/api/games', (req, res) => {
@@ -230,7 +244,7 @@ if (NODE_ENV === 'development') {
 }
 
 app.post('/api/games', async (req, res) => {
-    const { wager, playerAddress } = req.body;
+    const { wager, playerAddress, bothLostPolicy = 'houseTakesAll' } = req.body;
     
     // Create game request
     
@@ -250,6 +264,10 @@ app.post('/api/games', async (req, res) => {
         return res.status(400).json({ error: 'Invalid wager amount. Must be between 0.022 and 10 SOL' });
     }
     
+    if (!BOTH_LOST_POLICIES.includes(bothLostPolicy)) {
+        return res.status(400).json({ error: 'Invalid both-lost policy' });
+    }
+    
     // Check if player already has an active game
     const existingGame = games.find(g => 
         g.players.includes(playerAddress) && 
@@ -262,12 +280,11 @@ app.post('/api/games', async (req, res) => {
     }
     
     const gameId = 'game_' + Date.now() + '_' + Math.random().toString(36).substr(2, 9);
-    const word = words[Math.floor(Math.random() * words.length)];
     
     // Create blockchain escrow and game with pending signature status
     try {
         console.log('🎮 Creating blockchain escrow for:', playerAddress, 'wager:', wager);
-        const escrowResult = await solanaClient.createGameEscrow(playerAddress, parseFloat(wager));
+        const escrowResult = await solanaClient.createGameEscrow(playerAddress, parseFloat(wager), bothLostPolicy);
         console.log('🔍 Escrow result:', escrowResult);
         
         if (!escrowResult.success) {
@@ -280,7 +297,8 @@ app.post('/api/games', async (req, res) => {
             wager: parseFloat(wager),
             players: [playerAddress],
             status: 'waiting',
-            word: word,
+            // Derived from both players' seeds once the opponent joins
+            word: null,
             createdAt: Date.now(),
             guesses: [],
             playerResults: {},
@@ -291,12 +309,14 @@ app.post('/api/games', async (req, res) => {
             escrowDetails: {
                 programId: escrowResult.programId,
                 gameAccount: escrowResult.gameAccount,
-                escrowAccount: escrowResult.escrowAccount,
                 escrowType: escrowResult.escrowType,
                 wagerAmount: escrowResult.wagerAmount,
                 transferAmount: escrowResult.transferAmount,
-                gameKeypair: escrowResult.gameKeypair,
-                escrowKeypair: escrowResult.escrowKeypair
+                nonce: escrowResult.nonce,
+                joinDeadline: escrowResult.joinDeadline,
+                guessLog: escrowResult.guessLog,
+                guessDelegate: escrowResult.guessDelegate,
+                bothLostPolicy: escrowResult.bothLostPolicy
             }
         };
         
@@ -324,7 +344,7 @@ app.post('/api/games', async (req, res) => {
 // Update blockchain transaction status
 app.post('/api/games/:gameId/confirm-blockchain', async (req, res) => {
     const { gameId } = req.params;
-    const { signature, success } = req.body;
+    const { signature, success, seed } = req.body;
     
     const game = games.find(g => g.id === gameId);
     if (!game) {
@@ -332,6 +352,12 @@ app.post('/api/games/:gameId/confirm-blockchain', async (req, res) => {
     }
     
     if (success) {
+        const creatorSeed = parseSeed(seed);
+        if (!creatorSeed) {
+            return res.status(400).json({ error: 'Invalid seed' });
+        }
+        gameSeeds.set(game.id, [creatorSeed]);
+        
         console.log(`✅ Blockchain transaction confirmed for game ${gameId}: ${signature}`);
         console.log(`⏳ Waiting 3 seconds for blockchain state to finalize...`);
         
@@ -451,7 +477,7 @@ app.post('/api/games/:gameId/join', async (req, res) => {
 // Confirm Player 2 joined after blockchain transaction
 app.post('/api/games/:gameId/confirm-join', async (req, res) => {
     const { gameId} = req.params;
-    const { signature, success, playerAddress } = req.body;
+    const { signature, success, seed, playerAddress } = req.body;
     
     const game = games.find(g => g.id === gameId);
     if (!game) {
@@ -462,9 +488,42 @@ app.post('/api/games/:gameId/confirm-join', async (req, res) => {
         console.log(`✅ Player 2 joined with blockchain confirmation: ${playerAddress}`);
         console.log(`✅ Join transaction signature: ${signature}`);
         
+        const opponentSeed = parseSeed(seed);
+        const creatorSeeds = gameSeeds.get(game.id);
+        if (!opponentSeed || !creatorSeeds) {
+            return res.status(400).json({ error: 'Invalid seed' });
+        }
+        
         // Wait a moment for blockchain state to finalize (confirmed commitment)
         await new Promise(resolve => setTimeout(resolve, 2000));
         
+        // The word comes from both seeds and the slot hash recorded at join, checked against the
+        // on-chain commitments; anyone can re-derive it once the seeds are revealed at settlement
+        const seeds = [creatorSeeds[0], opponentSeed];
+        const derived = await solanaClient.deriveAnswerIndex(game.escrowDetails, seeds);
+        if (!derived.success) {
+            console.error(`❌ Word derivation failed for game ${gameId}: ${derived.error}`);
+            return res.status(400).json({ error: 'Could not derive word: ' + derived.error });
+        }
+        const word = words[derived.answerIndex];
+        
+        // Commit to the secret word on-chain so settlement can prove which word was played
+        // Without the commitment the game can never settle, so it does not start
+        const salt = crypto.randomBytes(32);
+        const commitResult = await withRetries(() => solanaClient.commitWord(game.escrowDetails, word, salt));
+        if (!commitResult.success) {
+            console.error(`❌ Word commit failed for game ${gameId}: ${commitResult.error}`);
+            games = games.filter(g => g.id !== gameId);
+            gameSeeds.delete(game.id);
+            io.emit('gameRemoved', { gameId });
+            return res.status(500).json({
+                error: 'Could not start the game on-chain. Both wagers can be reclaimed with claim_stale_refund once the game times out.'
+            });
+        }
+        gameSeeds.set(game.id, seeds);
+        wordSalts.set(game.id, salt);
+        game.word = word;
+        
         // Add player to game
         game.players.push(playerAddress);
         game.status = 'playing';
@@ -628,6 +687,11 @@ app.post('/api/games/:gameId/guess', async (req, res) => {
         return res.status(400).json({ error: 'Guess must be exactly 5 letters' });
     }
     
+    const guessProof = guessDictionary.proofs[sanitizedGuess];
+    if (!guessProof) {
+        return res.status(400).json({ error: 'Not in word list' });
+    }
+    
     const game = games.find(g => g.id === gameId);
     if (!game) {
         return res.status(404).json({ error: 'Game not found' });
@@ -651,6 +715,17 @@ app.post('/api/games/:gameId/guess', async (req, res) => {
         timestamp: Date.now()
     };
     
+    // Mirror the guess into the on-chain guess log (the program enforces the 6-guess limit too)
+    // The guess only counts once it is logged, so game.guesses never diverges from what settle_verified replays
+    const guessNumber = game.guesses.filter(g => g.player === playerAddress).length + 1;
+    const logResult = await withRetries(
+        () => solanaClient.submitGuess(game.escrowDetails, playerAddress, sanitizedGuess, guessProof, guessNumber)
+    );
+    if (!logResult.success) {
+        console.error(`❌ On-chain guess log failed for game ${gameId}: ${logResult.error}`);
+        return res.status(503).json({ error: 'Could not record guess on-chain, please try again' });
+    }
+    
     game.guesses.push(guessData);
     
     // Check if guess is correct
@@ -662,7 +737,7 @@ app.post('/api/games/:gameId/guess', async (req, res) => {
         game.completedAt = Date.now();
         
         // Settle on blockchain with 2% fee
-        await settleGameOnBlockchain(game, playerAddress, false, false);
+        await settleGameOnBlockchain(game, true);
         
         try { 
             completedGames.push({ id: game.id, wager: game.wager, winner: game.winner || null, status: 'completed', completedAt: game.completedAt }); 
@@ -690,11 +765,11 @@ app.post('/api/games/:gameId/guess', async (req, res) => {
             if (winnerEntry) {
                 game.winner = winnerEntry[0];
                 // Normal win - 2% fee
-                await settleGameOnBlockchain(game, game.winner, false, false);
+                await settleGameOnBlockchain(game, true);
             } else {
-                // Both lost - entire pot to house
+                // Both lost - paid out by the game's both-lost policy
                 game.winner = null;
-                await settleGameOnBlockchain(game, null, false, true);
+                await settleGameOnBlockchain(game, true);
             }
             
             try { 
@@ -780,12 +855,13 @@ app.post('/api/games/:gameId/timeout', async (req, res) => {
         const winnerEntry = Object.entries(game.playerResults).find(([, r]) => r === 'win');
         if (winnerEntry) {
             game.winner = winnerEntry[0];
-            // Normal win - 2% fee
-            await settleGameOnBlockchain(game, game.winner, false, false);
+            // Normal win - 2% fee, decided by the guess log
+            await settleGameOnBlockchain(game, true);
         } else {
-            // Both timed out/lost - entire pot to house
+            // Both timed out/lost - paid out by the game's both-lost policy
+            // Not awaited: settle_game may have to wait for the round's time limit
             game.winner = null;
-            await settleGameOnBlockchain(game, null, false, true);
+            settleGameOnBlockchain(game);
         }
         
         try { 
@@ -0,0 +1,25 @@
+// answerIndex must agree byte for byte with wordle_rules::seed::answer_index and round_slot_hash
+// Same vectors as the tests in crates/wordle-rules/src/seed.rs
+
+const test = require('node:test');
+const assert = require('node:assert');
+const { answerIndex } = require('../real-solana-client');
+
+const seeds = [Buffer.alloc(32, 1), Buffer.alloc(32, 2)];
+const slotHash = Buffer.alloc(32, 3);
+const answerRoot = Buffer.alloc(32, 4);
+
+test('answer index matches the Rust vectors', () => {
+    assert.strictEqual(answerIndex(seeds, slotHash, answerRoot, 536), 438);
+    assert.strictEqual(answerIndex(seeds, slotHash, answerRoot, 536, 1), 245);
+    assert.strictEqual(answerIndex(seeds, slotHash, answerRoot, 536, 2), 166);
+    assert.strictEqual(answerIndex(seeds, slotHash, answerRoot, 536, 255), 29);
+    assert.strictEqual(answerIndex(seeds, slotHash, answerRoot, 0xffffffff), 219483253);
+    assert.strictEqual(answerIndex(seeds, slotHash, answerRoot, 0xffffffff, 1), 3591510782);
+    assert.strictEqual(answerIndex(seeds, slotHash, answerRoot, 1), 0);
+});
+
+test('seed order matters', () => {
+    const swapped = answerIndex([seeds[1], seeds[0]], slotHash, answerRoot, 0xffffffff);
+    assert.notStrictEqual(swapped, 219483253);
+});